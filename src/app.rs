@@ -1,6 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::Duration;
 
@@ -11,27 +11,53 @@ use crossterm::{
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 
+use crate::config::{BuildMode, Config, ConfigOverrides, WatchTarget};
 use crate::git::ChangedTest;
-use crate::model::{TestProject, TestStatus};
-use crate::parser::TestOutcome;
-use crate::runner::{DiscoveryEvent, ExecutorEvent, FileWatcher, TestExecutor};
-use crate::ui::{self, layout::{AppState, startup_art, random_startup_phrase, random_ready_phrase}, Pane, TestListItem};
+use crate::model::{apply_report, apply_result, diff_runs, summarize, ChangeKind, OutputLine, OutputSource, RepeatStats, RunSnapshot, Test, TestClass, TestProject, TestStatus};
+use crate::debugger::attach_command;
+use crate::editor::editor_command;
+use crate::parser::{declared_class_names, tested_class_names, Diagnostic, TestOutcome, TestReport};
+use crate::runner::{affected_projects, discover_projects_from_paths, DiscoveryEvent, ExecutionHandle, ExecutorEvent, FileWatcher, ProjectQueue, TestExecutor};
+use crate::ui::{self, ProjectRunStatus, layout::{AppState, startup_art, random_startup_phrase, random_ready_phrase}, Pane, TestListItem};
 
 pub fn run(
     projects: Vec<TestProject>,
     solution_dir: PathBuf,
     discovery_rx: mpsc::Receiver<DiscoveryEvent>,
     context: Option<String>,
+    overrides: ConfigOverrides,
 ) -> io::Result<()> {
-    run_with_preselected(projects, solution_dir, discovery_rx, Vec::new(), context)
+    run_with_preselected(projects, solution_dir, discovery_rx, Vec::new(), context, overrides)
 }
 
+/// `overrides` take precedence over the config file (`--parallel`, `--profile`).
 pub fn run_with_preselected(
     projects: Vec<TestProject>,
     solution_dir: PathBuf,
     discovery_rx: mpsc::Receiver<DiscoveryEvent>,
     preselected_tests: Vec<ChangedTest>,
     context: Option<String>,
+    overrides: ConfigOverrides,
+) -> io::Result<()> {
+    run_app(projects, solution_dir, discovery_rx, preselected_tests, context, false, overrides)
+}
+
+/// Browse previously recorded results (e.g. TRX files from CI) without running anything.
+pub fn run_view(projects: Vec<TestProject>, solution_dir: PathBuf, context: Option<String>) -> io::Result<()> {
+    // Results are already loaded, so discovery completes immediately
+    let (tx, discovery_rx) = mpsc::channel();
+    let _ = tx.send(DiscoveryEvent::Complete);
+    run_app(projects, solution_dir, discovery_rx, Vec::new(), context, true, ConfigOverrides::default())
+}
+
+fn run_app(
+    projects: Vec<TestProject>,
+    solution_dir: PathBuf,
+    mut discovery_rx: mpsc::Receiver<DiscoveryEvent>,
+    preselected_tests: Vec<ChangedTest>,
+    context: Option<String>,
+    view_only: bool,
+    overrides: ConfigOverrides,
 ) -> io::Result<()> {
     // Setup terminal
     enable_raw_mode()?;
@@ -46,12 +72,26 @@ pub fn run_with_preselected(
     state.status = "Discovering tests...".to_string();
     state.context = context;
     state.output_auto_scroll = true;
+    state.view_only = view_only;
+    if view_only {
+        prepare_view(&mut state);
+    } else {
+        match Config::load(&solution_dir) {
+            Ok(config) => state.config = config,
+            Err(e) => state.append_output(&format!("\n[Config] {} (using defaults)\n", e)),
+        }
+        if let Err(e) = overrides.apply(&mut state.config) {
+            state.append_output(&format!("\n[Config] {}\n", e));
+        }
+    }
     
     // Store preselected test names to match after discovery
     let preselected = preselected_tests;
     let filter_to_preselected = !preselected.is_empty();
 
-    let mut executor_rx: Option<mpsc::Receiver<ExecutorEvent>> = None;
+    let mut executor_rx: Option<ExecutionHandle> = None;
+    let mut solution_run: Option<SolutionRun> = None;
+    let mut repeat_run: Option<RepeatRun> = None;
     let mut file_watcher: Option<FileWatcher> = None;
 
     // Main loop
@@ -150,13 +190,16 @@ pub fn run_with_preselected(
             }
         }
 
-        // Check for file changes in watch mode
+        // Check for file changes in watch mode. Changes made during a run are
+        // picked up once it ends.
         if state.watch_mode {
-            if let Some(ref watcher) = file_watcher {
-                if watcher.try_recv() && executor_rx.is_none() && !state.discovering {
-                    state.dirty = true;
-                    state.append_output("\n[Watch] File change detected, running tests...\n");
-                    run_tests(&mut state, &mut executor_rx);
+            if let Some(ref mut watcher) = file_watcher {
+                if executor_rx.is_none() && solution_run.is_none() && !state.discovering && !state.view_only {
+                    let changed = watcher.changed_paths();
+                    if !changed.is_empty() {
+                        state.dirty = true;
+                        solution_run = start_watch_run(&mut state, watcher.root(), &changed);
+                    }
                 }
             }
         }
@@ -167,14 +210,28 @@ pub fn run_with_preselected(
                 state.dirty = true;
                 match event {
                     ExecutorEvent::OutputLine(line) => {
-                        let trimmed = line.trim();
-                        if trimmed.starts_with("Passed") || trimmed.starts_with("Failed") {
-                            // Increment progress instead of appending
-                            if let Some((completed, _)) = &mut state.test_progress {
-                                *completed += 1;
-                            }
+                        // The progress bar stands in for dotnet test's own output during
+                        // runs, but stderr (crashes, load failures) is always shown
+                        if state.test_progress.is_none() || line.source != OutputSource::Stdout {
+                            state.append_output_line(&line);
+                        }
+                    }
+                    ExecutorEvent::BuildStarted => {
+                        state.clear_diagnostics();
+                        state.append_output("\nBuilding...");
+                    }
+                    ExecutorEvent::BuildOutput(line) => {
+                        state.append_build_line(&line);
+                    }
+                    ExecutorEvent::TestFinished(result) => {
+                        // Increment progress instead of appending
+                        if let Some((completed, _)) = &mut state.test_progress {
+                            *completed += 1;
+                        }
+                        // Use running_project_idx to update the correct project, not the currently selected one
+                        if let Some(idx) = state.running_project_idx.or(state.project_state.selected()) {
+                            apply_live_result(&mut state, idx, &result);
                         }
-                        // Ignore other dotnet output lines
                     }
                     ExecutorEvent::BuildCompleted(success) => {
                         if success {
@@ -182,61 +239,99 @@ pub fn run_with_preselected(
                         } else {
                             state.append_output("\nBuild FAILED");
                         }
+                        append_diagnostics_hint(&mut state);
                         state.status = "Ready".to_string();
                         executor_rx = None;
                         break;
                     }
-                    ExecutorEvent::Completed(results) => {
+                    ExecutorEvent::DebuggerWaiting(pid) => {
+                        announce_debuggee(&mut state, pid);
+                    }
+                    ExecutorEvent::Coverage(report) => {
+                        let totals = report.totals();
+                        state.append_output(&format!("\n[Coverage] {}", totals.summary()));
+                        state.set_coverage(report, false);
+                    }
+                    ExecutorEvent::Completed(report) => {
                         // Track failed tests and count results
                         state.last_failed.clear();
-                        let mut passed = 0;
-                        let mut failed = 0;
-                        let mut skipped = 0;
-                        for result in &results {
-                            match result.outcome {
-                                TestOutcome::Passed => passed += 1,
-                                TestOutcome::Failed => {
-                                    failed += 1;
-                                    state.last_failed.insert(result.test_name.clone());
-                                }
-                                TestOutcome::Skipped => skipped += 1,
-                            }
-                        }
+                        let (passed, failed, skipped) = record_results(&mut state, &report);
 
-                        apply_results(&mut state, &results);
+                        // Use running_project_idx to update the correct project, not the currently selected one
+                        let ran = match state.running_project_idx.take().or(state.project_state.selected()) {
+                            Some(idx) => apply_project_report(&mut state, idx, &report),
+                            None => HashSet::new(),
+                        };
                         // Reset any tests still stuck in RUNNING (no TRX result matched)
                         reset_unmatched_running_tests(&mut state);
-                        state.invalidate_test_items();
 
                         // Show summary
                         let total = passed + failed + skipped;
-                        let mut summary = format!("\n{} tests run.", total);
-                        if passed > 0 {
-                            summary.push_str(&format!(" {}/{} passed.", passed, total));
-                        }
-                        if failed > 0 {
-                            summary.push_str(&format!(" {} failed.", failed));
-                        }
-                        if skipped > 0 {
-                            summary.push_str(&format!(" {} skipped.", skipped));
-                        }
-                        state.append_output(&summary);
+                        state.append_output(&format!(
+                            "\n{} tests run.{}",
+                            total,
+                            outcome_summary(passed, failed, skipped)
+                        ));
+                        report_changes(&mut state, &ran);
 
                         state.test_progress = None;
                         state.status = "Ready".to_string();
                         executor_rx = None;
+                        if let Some(ref mut run) = repeat_run {
+                            executor_rx = continue_repeat_run(&mut state, run, &ran);
+                            if executor_rx.is_none() {
+                                repeat_run = None;
+                            }
+                        }
                         break;
                     }
                     ExecutorEvent::Error(e) => {
+                        state.live_results.clear();
+                        reset_unmatched_running_tests(&mut state);
+                        state.running_project_idx = None;
+                        state.invalidate_test_items();
                         state.append_output(&format!("\nError: {}", e));
+                        append_diagnostics_hint(&mut state);
+                        state.test_progress = None;
                         state.status = "Ready".to_string();
                         executor_rx = None;
+                        if let Some(run) = repeat_run.take() {
+                            finish_repeat_run(&mut state, &run);
+                        }
+                        break;
+                    }
+                    ExecutorEvent::Cancelled => {
+                        // Tests that finished before the cancel keep their live result
+                        state.live_results.clear();
+                        reset_unmatched_running_tests(&mut state);
+                        state.running_project_idx = None;
+                        state.invalidate_test_items();
+                        state.append_output("\nCancelled.");
+                        state.test_progress = None;
+                        state.status = "Ready".to_string();
+                        executor_rx = None;
+                        if let Some(run) = repeat_run.take() {
+                            finish_repeat_run(&mut state, &run);
+                        }
                         break;
                     }
                 }
             }
         }
 
+        // Check solution-wide run events
+        if let Some(ref mut run) = solution_run {
+            if poll_solution_run(&mut state, run) {
+                let then = run.then.take().filter(|_| !run.cancelled);
+                solution_run = then.and_then(|filters| {
+                    state.append_output("\n[Watch] Running the rest of the tests...");
+                    start_project_runs(&mut state, filters, BuildMode::Never)
+                });
+            }
+        }
+
+        state.busy = executor_rx.is_some() || solution_run.is_some();
+
         // Only redraw when state has changed
         if state.dirty {
             terminal.draw(|f| ui::draw(f, &mut state))?;
@@ -244,7 +339,7 @@ pub fn run_with_preselected(
         }
 
         // Dynamic timeout: fast when active operations are running, slow when idle
-        let poll_timeout = if state.discovering || executor_rx.is_some() {
+        let poll_timeout = if state.discovering || state.busy {
             Duration::from_millis(33)
         } else {
             Duration::from_millis(250)
@@ -279,8 +374,30 @@ pub fn run_with_preselected(
                         continue;
                     }
 
+                    // Nothing running, so a new build or run can start
+                    let idle = executor_rx.is_none() && solution_run.is_none() && !state.discovering && !state.view_only;
+
                     match key.code {
-                        KeyCode::Char('q') => break,
+                        KeyCode::Char('q') => {
+                            // Don't leave dotnet/testhost running after we exit
+                            if let Some(ref handle) = executor_rx {
+                                handle.cancel();
+                            }
+                            if let Some(ref mut run) = solution_run {
+                                cancel_solution_run(&mut state, run);
+                            }
+                            break;
+                        }
+                        KeyCode::Char('k') if executor_rx.is_some() || solution_run.is_some() => {
+                            if let Some(ref handle) = executor_rx {
+                                handle.cancel();
+                            }
+                            if let Some(ref mut run) = solution_run {
+                                cancel_solution_run(&mut state, run);
+                            }
+                            state.append_output("\nCancelling...");
+                            state.status = "Cancelling...".to_string();
+                        }
                         KeyCode::Down => {
                             move_selection(&mut state, 1);
                         }
@@ -309,11 +426,67 @@ pub fn run_with_preselected(
                                 Pane::TestResult => Pane::Output,
                             };
                         }
-                        KeyCode::Char(' ') => {
-                            if state.active_pane == Pane::Tests {
-                                toggle_space_action(&mut state);
+                        KeyCode::Char('d') => {
+                            state.show_diagnostics = !state.show_diagnostics;
+                            state.show_coverage = false;
+                            if state.show_diagnostics && state.diagnostic_state.selected().is_none() {
+                                // First diagnostic, below its project header
+                                state.diagnostic_state.select((!state.diagnostics.is_empty()).then_some(1));
+                            }
+                        }
+                        KeyCode::Char('v') => {
+                            state.show_coverage = !state.show_coverage;
+                            state.show_diagnostics = false;
+                        }
+                        KeyCode::Char('p') if !state.config.profiles.is_empty() => {
+                            // Applies from the next build or run
+                            state.config.cycle_profile();
+                            let message = match state.config.active_profile() {
+                                Some((name, _)) => format!("\n[Profile] Using profile {}.", name),
+                                None => "\n[Profile] No profile.".to_string(),
+                            };
+                            state.append_output(&message);
+                        }
+                        KeyCode::Char('B') if idle => {
+                            // Discover the tests again from the new configuration, as at startup
+                            state.config.cycle_configuration();
+                            let paths = state.projects.iter().map(|p| p.path.clone()).collect();
+                            let discovery = discover_projects_from_paths(
+                                paths,
+                                state.config.configuration(),
+                                &state.config.runner.extra_args,
+                            );
+                            let configuration = state.config.configuration().unwrap_or_default().to_string();
+                            match discovery {
+                                Ok((_, rx)) => {
+                                    discovery_rx = rx;
+                                    state.discovering = true;
+                                    state.status = "Discovering tests...".to_string();
+                                    state.append_output(&format!(
+                                        "\n[Config] Building and running {}, discovering tests again.",
+                                        configuration
+                                    ));
+                                }
+                                Err(e) => state.append_output(&format!("\n[Config] {}: {}", configuration, e)),
                             }
                         }
+                        KeyCode::Char('V') => {
+                            state.config.runner.coverage = !state.config.runner.coverage;
+                            let message = if state.config.runner.coverage {
+                                "\n[Coverage] Collecting code coverage on the next runs (press v to view it)."
+                            } else {
+                                "\n[Coverage] No longer collecting code coverage."
+                            };
+                            state.append_output(message);
+                        }
+                        KeyCode::Enter if state.show_diagnostics && state.active_pane == Pane::Output => {
+                            if let Some(diagnostic) = state.selected_diagnostic().cloned() {
+                                open_diagnostic(&mut terminal, &mut state, &solution_dir, &diagnostic)?;
+                            }
+                        }
+                        KeyCode::Char(' ') if state.active_pane == Pane::Tests => {
+                            toggle_space_action(&mut state);
+                        }
                         KeyCode::Char('x') => {
                             state.clear_output();
                             state.test_progress = None;
@@ -338,15 +511,13 @@ pub fn run_with_preselected(
                             state.filter_active = true;
                             state.filter.clear();
                         }
-                        KeyCode::Esc => {
-                            if !state.filter.is_empty() {
-                                state.filter.clear();
-                            }
+                        KeyCode::Esc if !state.filter.is_empty() => {
+                            state.filter.clear();
                         }
-                        KeyCode::Char('w') => {
+                        KeyCode::Char('w') if !state.view_only => {
                             state.watch_mode = !state.watch_mode;
                             if state.watch_mode {
-                                match FileWatcher::new(&solution_dir) {
+                                match FileWatcher::new(&solution_dir, &state.config.watch) {
                                     Ok(watcher) => {
                                         file_watcher = Some(watcher);
                                         state.append_output("\n[Watch] Watch mode enabled\n");
@@ -364,48 +535,60 @@ pub fn run_with_preselected(
                                 state.append_output("\n[Watch] Watch mode disabled\n");
                             }
                         }
-                        KeyCode::Char('r') => {
-                            if executor_rx.is_none() && !state.discovering {
-                                // If tests are multi-selected, run those
-                                if !state.selected_tests.is_empty() {
-                                    run_tests(&mut state, &mut executor_rx);
+                        KeyCode::Char('W') => {
+                            // Applies from the next change
+                            let target = state.config.watch.target.next();
+                            state.config.watch.target = target;
+                            state.append_output(&format!("\n[Watch] Changes run: {}", watch_target_description(target)));
+                        }
+                        KeyCode::Char('r') if idle => {
+                            // If tests are multi-selected, run those
+                            if !state.selected_tests.is_empty() {
+                                run_tests(&mut state, &mut executor_rx);
+                                continue;
+                            }
+
+                            // If in Tests pane, check what's under cursor
+                            if state.active_pane == Pane::Tests {
+                                // Check if a class is selected - run all tests in that class
+                                if let Some(class_tests) = get_selected_class_tests(&mut state) {
+                                    run_class_tests(&mut state, &mut executor_rx, class_tests);
                                     continue;
                                 }
 
-                                // If in Tests pane, check what's under cursor
-                                if state.active_pane == Pane::Tests {
-                                    // Check if a class is selected - run all tests in that class
-                                    if let Some(class_tests) = get_selected_class_tests(&mut state) {
-                                        run_class_tests(&mut state, &mut executor_rx, class_tests);
-                                        continue;
-                                    }
-
-                                    // Check if a single test is selected - run just that test
-                                    if let Some(test_name) = get_selected_single_test(&mut state) {
-                                        run_class_tests(&mut state, &mut executor_rx, vec![test_name]);
-                                        continue;
-                                    }
+                                // Check if a single test is selected - run just that test
+                                if let Some(test_name) = get_selected_single_test(&mut state) {
+                                    run_class_tests(&mut state, &mut executor_rx, vec![test_name]);
+                                    continue;
                                 }
-
-                                // Fallback: run all tests in project
-                                run_tests(&mut state, &mut executor_rx);
                             }
+
+                            // Fallback: run all tests in project
+                            run_tests(&mut state, &mut executor_rx);
                         }
-                        KeyCode::Char('R') => {
+                        KeyCode::Char('R') if idle => {
                             // Shift+R: always run all tests in the project
-                            if executor_rx.is_none() && !state.discovering {
-                                run_tests(&mut state, &mut executor_rx);
-                            }
+                            run_tests(&mut state, &mut executor_rx);
                         }
-                        KeyCode::Char('b') => {
-                            if executor_rx.is_none() && !state.discovering {
-                                build_project(&mut state, &mut executor_rx);
-                            }
+                        KeyCode::Char('S') if idle => {
+                            // Shift+S: run every project in the solution
+                            let all: Vec<usize> = (0..state.projects.len()).collect();
+                            let build = state.config.runner.build;
+                            solution_run = start_solution_run(&mut state, &all, build);
                         }
-                        KeyCode::Char('a') => {
-                            if executor_rx.is_none() && !state.discovering && !state.last_failed.is_empty() {
-                                run_failed_tests(&mut state, &mut executor_rx);
-                            }
+                        KeyCode::Char('f') | KeyCode::Char('F') if idle => {
+                            // Shift+F stops at the first failure
+                            let until_failure = key.code == KeyCode::Char('F');
+                            repeat_run = start_repeat_run(&mut state, &mut executor_rx, until_failure);
+                        }
+                        KeyCode::Char('D') if idle => {
+                            start_debug_run(&mut state, &mut executor_rx);
+                        }
+                        KeyCode::Char('b') if idle => {
+                            build_project(&mut state, &mut executor_rx);
+                        }
+                        KeyCode::Char('a') if idle && !state.last_failed.is_empty() => {
+                            run_failed_tests(&mut state, &mut executor_rx);
                         }
                         _ => {}
                     }
@@ -421,6 +604,410 @@ pub fn run_with_preselected(
     Ok(())
 }
 
+/// Tests to run per project (None runs the whole project)
+type ProjectFilters = HashMap<usize, Option<Vec<String>>>;
+
+/// A solution-wide run: projects are started from a queue, each with its own
+/// executor, at most `[runner] parallel` (or `--parallel`) at a time.
+struct SolutionRun {
+    queue: ProjectQueue,
+    active: Vec<(usize, ExecutionHandle)>,
+    filters: ProjectFilters,
+    totals: (usize, usize, usize),
+    project_count: usize,
+    cancelled: bool,
+    /// A project sent coverage; the others' is added to it
+    coverage_received: bool,
+    /// When each project is built before its tests run
+    build: BuildMode,
+    /// Tests run once this run is over, without building again
+    then: Option<ProjectFilters>,
+}
+
+/// Start running `projects`, or only those of them containing selected tests.
+fn start_solution_run(state: &mut AppState, projects: &[usize], build: BuildMode) -> Option<SolutionRun> {
+    let filters = if state.selected_tests.is_empty() {
+        projects
+            .iter()
+            .filter(|&&idx| state.projects.get(idx).is_some_and(|p| p.test_count() > 0))
+            .map(|&idx| (idx, None))
+            .collect()
+    } else {
+        filter_projects(state, projects, |_, test| state.selected_tests.contains(&test.full_name))
+    };
+    start_project_runs(state, filters, build)
+}
+
+/// The tests of `projects` that `keep` accepts, for the projects that have any.
+fn filter_projects(state: &AppState, projects: &[usize], keep: impl Fn(&TestClass, &Test) -> bool) -> ProjectFilters {
+    let mut filters = HashMap::new();
+    for &idx in projects {
+        let Some(project) = state.projects.get(idx) else { continue };
+        let tests: Vec<String> = project
+            .classes
+            .iter()
+            .flat_map(|class| class.tests.iter().filter(|test| keep(class, test)))
+            .map(|test| test.full_name.clone())
+            .collect();
+        if !tests.is_empty() {
+            filters.insert(idx, Some(tests));
+        }
+    }
+    filters
+}
+
+/// Mark the tests of `filters` running and start their projects.
+fn start_project_runs(state: &mut AppState, filters: ProjectFilters, build: BuildMode) -> Option<SolutionRun> {
+    if filters.is_empty() {
+        state.append_output("\nNo tests to run.\n");
+        return None;
+    }
+    let mut running = HashSet::new();
+    for (&idx, filter) in &filters {
+        let Some(project) = state.projects.get_mut(idx) else { continue };
+        let names: Option<HashSet<&String>> = filter.as_ref().map(|tests| tests.iter().collect());
+        for test in project.classes.iter_mut().flat_map(|c| c.tests.iter_mut()) {
+            if names.as_ref().is_none_or(|names| names.contains(&test.full_name)) {
+                test.status = TestStatus::Running;
+                running.insert(test.full_name.clone());
+            }
+        }
+    }
+    let total_tests = running.len();
+
+    let mut order: Vec<usize> = filters.keys().copied().collect();
+    order.sort_unstable();
+    let parallel = state.config.runner.parallel.resolve();
+
+    // Failures of tests that aren't run again are kept, e.g. for the second half of failed-first
+    state.last_failed.retain(|name| !running.contains(name));
+    state.clear_diagnostics();
+    state.project_runs = order.iter().map(|&idx| (idx, ProjectRunStatus::Queued)).collect();
+    state.output_auto_scroll = true;
+    state.append_output("\n────────────────────────────\n");
+    state.append_output(&format!(
+        "Running {} project(s), {} at a time...",
+        order.len(),
+        parallel
+    ));
+    state.test_progress = Some((0, total_tests));
+    state.status = "Running solution...".to_string();
+
+    let mut run = SolutionRun {
+        queue: ProjectQueue::new(order.iter().copied(), parallel),
+        active: Vec::new(),
+        filters,
+        totals: (0, 0, 0),
+        project_count: order.len(),
+        cancelled: false,
+        coverage_received: false,
+        build,
+        then: None,
+    };
+    start_queued_projects(state, &mut run);
+    Some(run)
+}
+
+/// Rebuild the test projects owning or depending on changed files and rerun
+/// the tests `[watch] target` picks in them.
+fn start_watch_run(state: &mut AppState, watch_root: &Path, changed: &[PathBuf]) -> Option<SolutionRun> {
+    const SHOWN: usize = 3;
+    let mut names: Vec<String> = changed
+        .iter()
+        .take(SHOWN)
+        .map(|path| path.strip_prefix(watch_root).unwrap_or(path).display().to_string())
+        .collect();
+    if changed.len() > SHOWN {
+        names.push(format!("{} more", changed.len() - SHOWN));
+    }
+    state.append_output(&format!("\n[Watch] Changed: {}", names.join(", ")));
+
+    let paths: Vec<PathBuf> = state.projects.iter().map(|project| project.path.clone()).collect();
+    let affected = affected_projects(&paths, changed);
+    if affected.is_empty() {
+        state.append_output("\n[Watch] No test project depends on the changed files.\n");
+        return None;
+    }
+    let target = state.config.watch.target;
+    let (filters, then) = watch_filters(state, target, &affected, changed);
+    if filters.is_empty() {
+        state.append_output(&format!("\n[Watch] No tests to run for target {}.\n", target));
+        return None;
+    }
+    let mut run = start_project_runs(state, filters, BuildMode::Always)?;
+    run.then = then;
+    // The file that triggered the run stays visible while it builds
+    state.status = format!("Running tests ({} changed)...", names[0]);
+    Some(run)
+}
+
+fn watch_target_description(target: WatchTarget) -> &'static str {
+    match target {
+        WatchTarget::Project => "every test of the affected projects",
+        WatchTarget::Selection => "the selected tests, or the class or test under the cursor",
+        WatchTarget::FailedFirst => "previously failed tests first, then the rest",
+        WatchTarget::ChangedClasses => "the test classes of the changed classes",
+    }
+}
+
+/// Tests a watch run runs in the `affected` projects. Failed-first also returns
+/// the rest of the tests, run afterwards.
+fn watch_filters(
+    state: &mut AppState,
+    target: WatchTarget,
+    affected: &[usize],
+    changed: &[PathBuf],
+) -> (ProjectFilters, Option<ProjectFilters>) {
+    let whole = || affected.iter().map(|&idx| (idx, None)).collect();
+    match target {
+        WatchTarget::Project => (whole(), None),
+        WatchTarget::Selection => {
+            let wanted: HashSet<String> = match state.project_state.selected() {
+                Some(idx) => tests_under_cursor(state, idx).into_iter().collect(),
+                None => state.selected_tests.clone(),
+            };
+            (filter_projects(state, affected, |_, test| wanted.contains(&test.full_name)), None)
+        }
+        WatchTarget::FailedFirst => {
+            let failed = filter_projects(state, affected, |_, test| state.last_failed.contains(&test.full_name));
+            if failed.is_empty() {
+                return (whole(), None);
+            }
+            let mut rest = filter_projects(state, affected, |_, test| !state.last_failed.contains(&test.full_name));
+            // Projects without failures run in full
+            for idx in affected.iter().filter(|idx| !failed.contains_key(idx)) {
+                rest.insert(*idx, None);
+            }
+            (failed, Some(rest))
+        }
+        WatchTarget::ChangedClasses => {
+            let classes: HashSet<String> = changed.iter().flat_map(|path| declared_class_names(path)).collect();
+            let filters = filter_projects(state, affected, |class, _| {
+                classes.contains(&class.name) || tested_class_names(&class.name).any(|name| classes.contains(name))
+            });
+            (filters, None)
+        }
+    }
+}
+
+fn start_queued_projects(state: &mut AppState, run: &mut SolutionRun) {
+    while let Some(idx) = run.queue.start_next() {
+        let Some(project) = state.projects.get(idx) else {
+            run.queue.finish(idx);
+            continue;
+        };
+        let path = project.path.clone();
+        let filter = run.filters.remove(&idx).flatten();
+        let total = match &filter {
+            Some(tests) => tests.len(),
+            None => project.test_count(),
+        };
+        state.project_runs.insert(idx, ProjectRunStatus::Running { completed: 0, total });
+        let handle = executor_for(state, &path).with_build_mode(run.build).run(filter);
+        run.active.push((idx, handle));
+    }
+}
+
+/// Handle events from every project in flight. Returns true once the whole run is over.
+fn poll_solution_run(state: &mut AppState, run: &mut SolutionRun) -> bool {
+    let mut finished = Vec::new();
+    for (idx, handle) in &run.active {
+        let idx = *idx;
+        let name = state.projects.get(idx).map(|p| p.name.clone()).unwrap_or_default();
+        while let Ok(event) = handle.try_recv() {
+            state.dirty = true;
+            match event {
+                ExecutorEvent::OutputLine(line) if line.source == OutputSource::Stderr => {
+                    state.append_output_line(&OutputLine::new(line.source, format!("[{}] {}", name, line.content)));
+                }
+                ExecutorEvent::OutputLine(_) | ExecutorEvent::BuildCompleted(_) | ExecutorEvent::DebuggerWaiting(_) => {}
+                ExecutorEvent::BuildStarted => {
+                    state.append_output(&format!("\n[{}] Building...", name));
+                }
+                ExecutorEvent::BuildOutput(line) => {
+                    state.append_output_line(&OutputLine::new(line.source, format!("[{}] {}", name, line.content)));
+                    state.record_diagnostic(&line.content);
+                }
+                ExecutorEvent::TestFinished(result) => {
+                    if let Some((completed, _)) = &mut state.test_progress {
+                        *completed += 1;
+                    }
+                    if let Some(ProjectRunStatus::Running { completed, .. }) = state.project_runs.get_mut(&idx) {
+                        *completed += 1;
+                    }
+                    apply_live_result(state, idx, &result);
+                }
+                ExecutorEvent::Coverage(report) => {
+                    state.append_output(&format!("\n[{}] [Coverage] {}", name, report.totals().summary()));
+                    state.set_coverage(report, run.coverage_received);
+                    run.coverage_received = true;
+                }
+                ExecutorEvent::Completed(report) => {
+                    let (passed, failed, skipped) = record_results(state, &report);
+                    let ran = apply_project_report(state, idx, &report);
+                    run.totals.0 += passed;
+                    run.totals.1 += failed;
+                    run.totals.2 += skipped;
+                    state.project_runs.insert(idx, ProjectRunStatus::Done { passed, failed, skipped });
+                    state.append_output(&format!(
+                        "\n[{}] {} tests run.{}",
+                        name,
+                        passed + failed + skipped,
+                        outcome_summary(passed, failed, skipped)
+                    ));
+                    report_changes(state, &ran);
+                    finished.push(idx);
+                    break;
+                }
+                ExecutorEvent::Error(e) => {
+                    end_project_run(state, idx, ProjectRunStatus::Error);
+                    state.append_output(&format!("\n[{}] Error: {}", name, e));
+                    finished.push(idx);
+                    break;
+                }
+                ExecutorEvent::Cancelled => {
+                    end_project_run(state, idx, ProjectRunStatus::Cancelled);
+                    finished.push(idx);
+                    break;
+                }
+            }
+        }
+    }
+
+    for idx in finished {
+        run.active.retain(|(i, _)| *i != idx);
+        run.queue.finish(idx);
+    }
+    if !run.cancelled {
+        start_queued_projects(state, run);
+    }
+    if !run.queue.is_done() {
+        return false;
+    }
+
+    let (passed, failed, skipped) = run.totals;
+    let mut summary = format!(
+        "\nSolution: {} project(s), {} tests run.{}",
+        run.project_count,
+        passed + failed + skipped,
+        outcome_summary(passed, failed, skipped)
+    );
+    if run.cancelled {
+        summary.push_str(" Cancelled.");
+    }
+    state.append_output(&summary);
+    append_diagnostics_hint(state);
+    state.test_progress = None;
+    state.status = "Ready".to_string();
+    true
+}
+
+/// Stop a solution-wide run: kill projects in flight and skip those still queued.
+fn cancel_solution_run(state: &mut AppState, run: &mut SolutionRun) {
+    run.cancelled = true;
+    for (_, handle) in &run.active {
+        handle.cancel();
+    }
+    for idx in run.queue.clear_pending() {
+        end_project_run(state, idx, ProjectRunStatus::Cancelled);
+    }
+}
+
+/// A project's run ended without results: its tests go back to not run.
+fn end_project_run(state: &mut AppState, idx: usize, status: ProjectRunStatus) {
+    if let Some(project) = state.projects.get_mut(idx) {
+        for class in &mut project.classes {
+            for test in &mut class.tests {
+                // Tests that finished before keep their live result
+                state.live_results.remove(&test.full_name);
+            }
+        }
+        reset_running_tests(project);
+    }
+    state.project_runs.insert(idx, status);
+    state.invalidate_test_items();
+}
+
+/// Point at the diagnostics view after a build that reported errors or warnings.
+fn append_diagnostics_hint(state: &mut AppState) {
+    if state.diagnostics.is_empty() || state.show_diagnostics {
+        return;
+    }
+    let (errors, warnings) = ui::count_severities(&state.diagnostics);
+    state.append_output(&format!(
+        "\n{} (press d for details)",
+        ui::describe_counts(errors, warnings)
+    ));
+}
+
+/// Open the file of a build diagnostic at its line, suspending the TUI while a
+/// terminal editor runs.
+fn open_diagnostic<B: ratatui::backend::Backend + io::Write>(
+    terminal: &mut Terminal<B>,
+    state: &mut AppState,
+    solution_dir: &Path,
+    diagnostic: &Diagnostic,
+) -> io::Result<()> {
+    let Some(line) = diagnostic.line else {
+        state.append_output(&format!("\n{} has no source location to open.", diagnostic.file));
+        return Ok(());
+    };
+    // MSBuild prints full paths; anything relative is taken from the solution directory
+    let file = solution_dir.join(&diagnostic.file);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    let status = editor_command(&file, line, diagnostic.column.unwrap_or(1)).status();
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    terminal.clear()?;
+
+    if let Err(e) = status {
+        state.append_output(&format!("\nFailed to start editor: {}", e));
+    }
+    Ok(())
+}
+
+/// Summarise loaded results and collapse classes without failures so they stand out.
+fn prepare_view(state: &mut AppState) {
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+    for project in &state.projects {
+        for class in &project.classes {
+            let mut class_failed = false;
+            for test in &class.tests {
+                match test.status {
+                    TestStatus::Passed => passed += 1,
+                    TestStatus::Failed => {
+                        failed += 1;
+                        class_failed = true;
+                    }
+                    TestStatus::Skipped => skipped += 1,
+                    _ => {}
+                }
+            }
+            if !class_failed {
+                state.collapsed_classes.insert(AppState::collapse_key(&project.name, &class.full_name));
+            }
+        }
+    }
+    state.invalidate_test_items();
+
+    let total = passed + failed + skipped;
+    let mut summary = format!("\nLoaded {} results.", total);
+    if passed > 0 {
+        summary.push_str(&format!(" {}/{} passed.", passed, total));
+    }
+    if failed > 0 {
+        summary.push_str(&format!(" {} failed.", failed));
+    }
+    if skipped > 0 {
+        summary.push_str(&format!(" {} skipped.", skipped));
+    }
+    state.append_output(&summary);
+}
+
 fn move_selection(state: &mut AppState, delta: i32) {
     match state.active_pane {
         Pane::Projects => {
@@ -441,6 +1028,24 @@ fn move_selection(state: &mut AppState, delta: i32) {
             let new = (current + delta).rem_euclid(len as i32) as usize;
             state.test_state.select(Some(new));
         }
+        Pane::Output if state.show_coverage => {
+            let len = state.coverage_items().len();
+            if len == 0 {
+                return;
+            }
+            let current = state.coverage_state.selected().unwrap_or(0) as i32;
+            let new = (current + delta).rem_euclid(len as i32) as usize;
+            state.coverage_state.select(Some(new));
+        }
+        Pane::Output if state.show_diagnostics => {
+            let len = state.diagnostic_items().len();
+            if len == 0 {
+                return;
+            }
+            let current = state.diagnostic_state.selected().unwrap_or(0) as i32;
+            let new = (current + delta).rem_euclid(len as i32) as usize;
+            state.diagnostic_state.select(Some(new));
+        }
         Pane::Output => {
             let line_count = state.output_newline_count as i32;
             let current = state.output_scroll as i32;
@@ -502,7 +1107,7 @@ fn toggle_space_action(state: &mut AppState) {
 
 fn run_tests(
     state: &mut AppState,
-    executor_rx: &mut Option<std::sync::mpsc::Receiver<ExecutorEvent>>,
+    executor_rx: &mut Option<ExecutionHandle>,
 ) {
     if let Some(idx) = state.project_state.selected() {
         // Get path before mutating
@@ -546,14 +1151,211 @@ fn run_tests(
         state.test_progress = Some((0, total_tests));
         state.status = "Running tests...".to_string();
 
-        let executor = TestExecutor::new(&path);
+        let executor = executor_for(state, &path);
         *executor_rx = Some(executor.run(tests_to_run));
     }
 }
 
+/// The same tests run several times in a row (`f`), to measure how flaky they are.
+struct RepeatRun {
+    idx: usize,
+    tests: Vec<String>,
+    count: u32,
+    completed: u32,
+    until_failure: bool,
+}
+
+/// Start repeating the tests `r` would run: the selection, or the class or test
+/// under the cursor, or the whole project. Runs `[runner] repeat` times.
+fn start_repeat_run(
+    state: &mut AppState,
+    executor_rx: &mut Option<ExecutionHandle>,
+    until_failure: bool,
+) -> Option<RepeatRun> {
+    let idx = state.project_state.selected()?;
+    let tests = tests_under_cursor(state, idx);
+    if tests.is_empty() {
+        state.append_output("\nNo tests to repeat.\n");
+        return None;
+    }
+
+    // Statistics are per repeated run
+    let names: HashSet<&String> = tests.iter().collect();
+    if let Some(project) = state.projects.get_mut(idx) {
+        for test in project.classes.iter_mut().flat_map(|c| c.tests.iter_mut()) {
+            if names.contains(&test.full_name) {
+                test.repeat = Some(RepeatStats::default());
+            }
+        }
+    }
+
+    let run = RepeatRun {
+        idx,
+        tests,
+        count: state.config.runner.repeat_count(),
+        completed: 0,
+        until_failure,
+    };
+    *executor_rx = Some(start_repeat_iteration(state, &run));
+    Some(run)
+}
+
+/// The tests `r` would run: the selection, or the class or test under the
+/// cursor, or else the whole project.
+fn tests_under_cursor(state: &mut AppState, idx: usize) -> Vec<String> {
+    let tests = if !state.selected_tests.is_empty() {
+        state.selected_tests.iter().cloned().collect()
+    } else if state.active_pane == Pane::Tests {
+        get_selected_class_tests(state)
+            .or_else(|| get_selected_single_test(state).map(|test| vec![test]))
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    if !tests.is_empty() {
+        return tests;
+    }
+    state.projects.get(idx)
+        .map(|project| project.classes.iter().flat_map(|c| &c.tests).map(|t| t.full_name.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// Mark `tests` of project `idx` running. Returns the project path.
+fn mark_running(state: &mut AppState, idx: usize, tests: &[String]) -> PathBuf {
+    let names: HashSet<&String> = tests.iter().collect();
+    let mut path = PathBuf::new();
+    if let Some(project) = state.projects.get_mut(idx) {
+        path = project.path.clone();
+        for test in project.classes.iter_mut().flat_map(|c| c.tests.iter_mut()) {
+            if names.contains(&test.full_name) {
+                test.status = TestStatus::Running;
+            }
+        }
+    }
+    state.running_project_idx = Some(idx);
+    state.invalidate_test_items();
+    path
+}
+
+fn start_repeat_iteration(state: &mut AppState, run: &RepeatRun) -> ExecutionHandle {
+    let path = mark_running(state, run.idx, &run.tests);
+
+    state.output_auto_scroll = true;
+    state.append_output("\n────────────────────────────\n");
+    state.append_output(&format!(
+        "Repeat {}/{}: running {} test(s)...",
+        run.completed + 1,
+        run.count,
+        run.tests.len()
+    ));
+    state.test_progress = Some((0, run.tests.len()));
+    state.status = format!("Repeating tests ({}/{})...", run.completed + 1, run.count);
+
+    executor_for(state, &path).run(Some(run.tests.clone()))
+}
+
+/// Run the tests `r` would run with `VSTEST_HOST_DEBUG=1` (`D`). testhost waits
+/// for a debugger before running them; see `announce_debuggee`.
+fn start_debug_run(state: &mut AppState, executor_rx: &mut Option<ExecutionHandle>) {
+    let Some(idx) = state.project_state.selected() else { return };
+    let tests = tests_under_cursor(state, idx);
+    if tests.is_empty() {
+        state.append_output("\nNo tests to debug.\n");
+        return;
+    }
+    let path = mark_running(state, idx, &tests);
+
+    state.output_auto_scroll = true;
+    state.append_output("\n────────────────────────────\n");
+    state.append_output(&format!("Debugging {} test(s), waiting for testhost to start...", tests.len()));
+    state.test_progress = Some((0, tests.len()));
+    state.status = "Debugging...".to_string();
+
+    *executor_rx = Some(executor_for(state, &path).with_debug(true).run(Some(tests)));
+}
+
+/// testhost is waiting for a debugger: show its process id, and start the
+/// `[runner] debug_attach` command if there is one.
+fn announce_debuggee(state: &mut AppState, pid: u32) {
+    state.append_output(&format!(
+        "\n\n[Debug] testhost is waiting for a debugger: attach to Process Id {}\n\
+         [Debug] The tests run once the debugger is attached. Press k to cancel.",
+        pid
+    ));
+    state.status = format!("Attach debugger to PID {}", pid);
+
+    let Some(template) = state.config.runner.debug_attach.clone() else { return };
+    let Some(mut cmd) = attach_command(&template, pid) else { return };
+    match cmd.spawn() {
+        Ok(mut child) => {
+            state.append_output(&format!("\n[Debug] Started: {}", template.replace("{pid}", &pid.to_string())));
+            // Reap the attach command whenever it exits
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => state.append_output(&format!("\n[Debug] Failed to start debug_attach command: {}", e)),
+    }
+}
+
+/// Count the results of the run that just completed, then start the next one.
+/// Returns None once the repeated run is over.
+fn continue_repeat_run(state: &mut AppState, run: &mut RepeatRun, ran: &HashSet<String>) -> Option<ExecutionHandle> {
+    run.completed += 1;
+    let mut any_failed = false;
+    if let Some(project) = state.projects.get_mut(run.idx) {
+        for test in project.classes.iter_mut().flat_map(|c| c.tests.iter_mut()) {
+            if ran.contains(&test.full_name) {
+                any_failed |= test.status == TestStatus::Failed;
+                test.repeat.get_or_insert_with(RepeatStats::default).record(&test.status, test.duration_ms);
+            }
+        }
+    }
+
+    if run.completed < run.count && !(run.until_failure && any_failed) {
+        return Some(start_repeat_iteration(state, run));
+    }
+    finish_repeat_run(state, run);
+    None
+}
+
+/// Summarize a repeated run: which tests were flaky, and how often they failed.
+fn finish_repeat_run(state: &mut AppState, run: &RepeatRun) {
+    let names: HashSet<&String> = run.tests.iter().collect();
+    let mut flaky = Vec::new();
+    let mut always_failed = 0;
+    if let Some(project) = state.projects.get(run.idx) {
+        for test in project.classes.iter().flat_map(|c| &c.tests) {
+            let Some(stats) = test.repeat.as_ref().filter(|_| names.contains(&test.full_name)) else {
+                continue;
+            };
+            if stats.is_flaky() {
+                flaky.push(format!("\n  {} - {}", test.full_name, stats.summary()));
+            } else if stats.failed > 0 {
+                always_failed += 1;
+            }
+        }
+    }
+
+    let mut summary = format!(
+        "\n[Repeat] {} of {} run(s) completed. {} flaky, {} failed every run.",
+        run.completed,
+        run.count,
+        flaky.len(),
+        always_failed
+    );
+    for line in flaky {
+        summary.push_str(&line);
+    }
+    state.append_output(&summary);
+}
+
+/// Executor for a project, configured from `.testament.toml`.
+fn executor_for(state: &AppState, project_path: &Path) -> TestExecutor {
+    TestExecutor::configured(project_path, &state.config)
+}
+
 fn build_project(
     state: &mut AppState,
-    executor_rx: &mut Option<std::sync::mpsc::Receiver<ExecutorEvent>>,
+    executor_rx: &mut Option<ExecutionHandle>,
 ) {
     if let Some(idx) = state.project_state.selected() {
         if let Some(project) = state.projects.get(idx) {
@@ -563,8 +1365,9 @@ fn build_project(
             state.append_output("\n────────────────────────────\n");
             state.append_output("Building...\n");
             state.status = "Building...".to_string();
+            state.clear_diagnostics();
 
-            let executor = TestExecutor::new(&path);
+            let executor = executor_for(state, &path);
             *executor_rx = Some(executor.build());
         }
     }
@@ -572,7 +1375,7 @@ fn build_project(
 
 fn run_failed_tests(
     state: &mut AppState,
-    executor_rx: &mut Option<std::sync::mpsc::Receiver<ExecutorEvent>>,
+    executor_rx: &mut Option<ExecutionHandle>,
 ) {
     if let Some(idx) = state.project_state.selected() {
         if let Some(project) = state.projects.get(idx) {
@@ -605,7 +1408,7 @@ fn run_failed_tests(
             state.test_progress = Some((0, failed_count));
             state.status = "Running tests...".to_string();
 
-            let executor = TestExecutor::new(&path);
+            let executor = executor_for(state, &path);
             let filter: Vec<String> = state.last_failed.iter().cloned().collect();
             *executor_rx = Some(executor.run(Some(filter)));
         }
@@ -671,120 +1474,178 @@ fn mark_filtered_tests_running(state: &mut AppState, filtered_tests: &HashSet<St
     }
 }
 
-fn apply_results(state: &mut AppState, results: &[crate::parser::TestResult]) {
-    use std::collections::HashMap;
+fn apply_results(state: &mut AppState, idx: usize, report: &TestReport) {
+    if let Some(project) = state.projects.get_mut(idx) {
+        apply_report(project, report);
+    }
+}
 
-    // Use running_project_idx to update the correct project, not the currently selected one
-    let idx = state.running_project_idx.or(state.project_state.selected());
-    if let Some(idx) = idx {
-        if let Some(project) = state.projects.get_mut(idx) {
-            // Build index: map from test_name -> Vec<(index, &result)>
-            // Also map from suffix (last segment after '.') -> Vec<(index, &result)>
-            let mut by_full_name: HashMap<&str, Vec<(usize, &crate::parser::TestResult)>> = HashMap::new();
-            let mut by_suffix: HashMap<&str, Vec<(usize, &crate::parser::TestResult)>> = HashMap::new();
-            let mut by_bare_name: HashMap<&str, Vec<(usize, &crate::parser::TestResult)>> = HashMap::new();
-
-            for (i, r) in results.iter().enumerate() {
-                by_full_name.entry(r.test_name.as_str()).or_default().push((i, r));
-                // Extract the part after the last '.' for suffix matching
-                if let Some(pos) = r.test_name.rfind('.') {
-                    let suffix = &r.test_name[pos + 1..];
-                    by_suffix.entry(suffix).or_default().push((i, r));
-                }
-                by_bare_name.entry(r.test_name.as_str()).or_default().push((i, r));
-            }
+/// Show a result reported on the console while the run is still going. Only
+/// unambiguous matches are applied; the final report reconciles everything.
+fn apply_live_result(state: &mut AppState, idx: usize, result: &crate::parser::TestResult) {
+    let Some(project) = state.projects.get_mut(idx) else {
+        return;
+    };
 
-            let mut consumed = vec![false; results.len()];
+    // xUnit prints fully-qualified names, NUnit/MSTest the bare method (plus arguments)
+    let name = result.test_name.as_str();
+    let base = name.split('(').next().unwrap_or(name);
+    let name_suffix = format!(".{}", name);
+    let base_suffix = format!(".{}", base);
+    let live = &mut state.live_results;
+    let mut candidates = project.classes.iter_mut()
+        .flat_map(|c| c.tests.iter_mut())
+        .filter(|t| t.status == TestStatus::Running || live.contains(&t.full_name))
+        .filter(|t| {
+            t.full_name == name
+                || t.full_name == base
+                || t.full_name.ends_with(&name_suffix)
+                || t.full_name.ends_with(&base_suffix)
+        });
+    let (Some(test), None) = (candidates.next(), candidates.next()) else {
+        return;
+    };
 
-            // Pass 1: precise full_name matching, then suffix matching
-            for class in &mut project.classes {
-                for test in &mut class.tests {
-                    // Try exact match first
-                    let matched = by_full_name.get(test.full_name.as_str())
-                        .and_then(|entries| entries.iter().find(|(i, _)| !consumed[*i]))
-                        .or_else(|| {
-                            // Try suffix match: result ends with ".{test.full_name}"
-                            by_suffix.get(test.full_name.as_str())
-                                .and_then(|entries| entries.iter().find(|(i, _)| !consumed[*i]))
-                        });
-
-                    if let Some(&(i, result)) = matched {
-                        consumed[i] = true;
-                        test.status = match result.outcome {
-                            TestOutcome::Passed => TestStatus::Passed,
-                            TestOutcome::Failed => TestStatus::Failed,
-                            TestOutcome::Skipped => TestStatus::Skipped,
-                        };
-                        test.duration_ms = Some(result.duration_ms);
-                        test.error_message = result.error_message.clone();
-                    }
-                }
+    // Several cases of one parameterized test: keep showing the first failure
+    if test.status == TestStatus::Failed && result.outcome != TestOutcome::Failed {
+        return;
+    }
+    apply_result(test, result);
+    live.insert(test.full_name.clone());
+}
+
+/// Put tests whose status came from console output back to Running, so the
+/// final report is matched against them like any other test in the run.
+fn restore_live_results(state: &mut AppState, idx: usize) {
+    if state.live_results.is_empty() {
+        return;
+    }
+    let Some(project) = state.projects.get_mut(idx) else {
+        return;
+    };
+    for class in &mut project.classes {
+        for test in &mut class.tests {
+            if state.live_results.remove(&test.full_name) {
+                test.status = TestStatus::Running;
             }
+        }
+    }
+}
 
-            // Pass 1.5: endsWith matching for multi-segment names (e.g. "Class.Method" vs "Namespace.Class.Method")
-            let suffix_needle = ".";
-            for class in &mut project.classes {
-                for test in &mut class.tests {
-                    if test.status != TestStatus::Running {
-                        continue;
-                    }
-                    let needle = format!("{}{}", suffix_needle, test.full_name);
-                    let matched = results.iter().enumerate()
-                        .find(|(i, r)| !consumed[*i] && (r.test_name.ends_with(&needle) || r.test_name == test.full_name));
-                    if let Some((i, result)) = matched {
-                        consumed[i] = true;
-                        test.status = match result.outcome {
-                            TestOutcome::Passed => TestStatus::Passed,
-                            TestOutcome::Failed => TestStatus::Failed,
-                            TestOutcome::Skipped => TestStatus::Skipped,
-                        };
-                        test.duration_ms = Some(result.duration_ms);
-                        test.error_message = result.error_message.clone();
-                    }
-                }
+fn running_test_names(state: &AppState, idx: usize) -> HashSet<String> {
+    state.projects.get(idx).into_iter()
+        .flat_map(|p| &p.classes)
+        .flat_map(|c| &c.tests)
+        .filter(|t| t.status == TestStatus::Running)
+        .map(|t| t.full_name.clone())
+        .collect()
+}
+
+/// Count outcomes in a report and remember its failures for rerunning with `a`.
+fn record_results(state: &mut AppState, report: &TestReport) -> (usize, usize, usize) {
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+    for result in &report.results {
+        match result.outcome {
+            TestOutcome::Passed => passed += 1,
+            TestOutcome::Failed => {
+                failed += 1;
+                let name = report
+                    .qualified_name(result)
+                    .unwrap_or_else(|| result.test_name.clone());
+                state.last_failed.insert(name);
             }
+            TestOutcome::Skipped => skipped += 1,
+        }
+    }
+    (passed, failed, skipped)
+}
 
-            // Pass 2: bare name fallback for remaining unmatched tests/results
-            for class in &mut project.classes {
-                for test in &mut class.tests {
-                    if test.status == TestStatus::Running {
-                        // Try test.name directly
-                        let matched = by_bare_name.get(test.name.as_str())
-                            .and_then(|entries| entries.iter().find(|(i, _)| !consumed[*i]))
-                            .or_else(|| {
-                                // Try bare method name (after last '.') for Class.Method style names
-                                let method = test.name.rsplit('.').next().unwrap_or(&test.name);
-                                by_bare_name.get(method)
-                                    .and_then(|entries| entries.iter().find(|(i, _)| !consumed[*i]))
-                            });
-
-                        if let Some(&(i, result)) = matched {
-                            consumed[i] = true;
-                            test.status = match result.outcome {
-                                TestOutcome::Passed => TestStatus::Passed,
-                                TestOutcome::Failed => TestStatus::Failed,
-                                TestOutcome::Skipped => TestStatus::Skipped,
-                            };
-                            test.duration_ms = Some(result.duration_ms);
-                            test.error_message = result.error_message.clone();
-                        }
-                    }
+/// Apply a finished project's report to its tests. Returns the names of the
+/// tests that were part of the run, for `report_changes`.
+fn apply_project_report(state: &mut AppState, idx: usize, report: &TestReport) -> HashSet<String> {
+    // The report is authoritative: let it re-match tests shown live
+    restore_live_results(state, idx);
+    let ran = running_test_names(state, idx);
+    apply_results(state, idx, report);
+    // Reset any tests still stuck in RUNNING (no result matched)
+    if let Some(project) = state.projects.get_mut(idx) {
+        reset_running_tests(project);
+    }
+    state.invalidate_test_items();
+    ran
+}
+
+/// " 12/14 passed. 2 failed." style summary of outcome counts
+fn outcome_summary(passed: usize, failed: usize, skipped: usize) -> String {
+    let total = passed + failed + skipped;
+    let mut summary = String::new();
+    if passed > 0 {
+        summary.push_str(&format!(" {}/{} passed.", passed, total));
+    }
+    if failed > 0 {
+        summary.push_str(&format!(" {} failed.", failed));
+    }
+    if skipped > 0 {
+        summary.push_str(&format!(" {} skipped.", skipped));
+    }
+    summary
+}
+
+/// Compare the tests that just ran with their previous results in this session,
+/// badge new failures and fixes, and summarize the changes in the output pane.
+fn report_changes(state: &mut AppState, ran: &HashSet<String>) {
+    let current = RunSnapshot::from_tests(
+        state.projects.iter()
+            .flat_map(|p| &p.classes)
+            .flat_map(|c| &c.tests)
+            .filter(|t| ran.contains(&t.full_name)),
+    );
+    // Nothing to compare against on the first run
+    let changes = if state.last_run.is_empty() {
+        Vec::new()
+    } else {
+        diff_runs(&state.last_run, &current)
+    };
+    state.last_run.merge(current);
+
+    let kinds: HashMap<&str, ChangeKind> = changes.iter().map(|c| (c.name.as_str(), c.kind)).collect();
+    for project in &mut state.projects {
+        for class in &mut project.classes {
+            for test in &mut class.tests {
+                if ran.contains(&test.full_name) {
+                    test.change = kinds.get(test.full_name.as_str()).copied();
                 }
             }
         }
     }
-    // Clear running project index after applying results
-    state.running_project_idx = None;
+
+    if changes.is_empty() {
+        return;
+    }
+    let summary: Vec<String> = summarize(&changes)
+        .iter()
+        .map(|(kind, count)| kind.describe(*count))
+        .collect();
+    state.append_output(&format!("\nSince last run: {}.", summary.join(", ")));
+    for change in changes.iter().filter(|c| c.kind == ChangeKind::NewFailure) {
+        state.append_output(&format!("\n  new failure: {}", change.name));
+    }
 }
 
 /// Reset any tests still in RUNNING state back to NotRun (no TRX result found for them)
 fn reset_unmatched_running_tests(state: &mut AppState) {
     for project in &mut state.projects {
-        for class in &mut project.classes {
-            for test in &mut class.tests {
-                if test.status == TestStatus::Running {
-                    test.status = TestStatus::NotRun;
-                }
+        reset_running_tests(project);
+    }
+}
+
+fn reset_running_tests(project: &mut TestProject) {
+    for class in &mut project.classes {
+        for test in &mut class.tests {
+            if test.status == TestStatus::Running {
+                test.status = TestStatus::NotRun;
             }
         }
     }
@@ -830,7 +1691,7 @@ fn get_selected_single_test(state: &mut AppState) -> Option<String> {
 /// Run tests for a specific class.
 fn run_class_tests(
     state: &mut AppState,
-    executor_rx: &mut Option<std::sync::mpsc::Receiver<ExecutorEvent>>,
+    executor_rx: &mut Option<ExecutionHandle>,
     tests: Vec<String>,
 ) {
     if let Some(idx) = state.project_state.selected() {
@@ -863,7 +1724,7 @@ fn run_class_tests(
         state.test_progress = Some((0, test_count));
         state.status = "Running tests...".to_string();
 
-        let executor = TestExecutor::new(&path);
+        let executor = executor_for(state, &path);
         *executor_rx = Some(executor.run(Some(tests)));
     }
 }
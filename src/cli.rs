@@ -1,6 +1,8 @@
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
+use crate::config::{ConfigOverrides, Parallelism};
+
 #[derive(Parser)]
 #[command(name = "testament")]
 #[command(about = "A TUI for discovering, running, and monitoring .NET tests")]
@@ -10,6 +12,26 @@ pub struct Cli {
     #[arg(value_name = "PATH")]
     pub path: Option<PathBuf>,
 
+    /// Projects to test at once in a solution-wide run: a number or "auto" (cores / 2)
+    #[arg(long, global = true, value_name = "N")]
+    pub parallel: Option<Parallelism>,
+
+    /// Run profile from [profiles] in .testament.toml to start with
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Build configuration to build, run and discover tests with (e.g. Release)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub configuration: Option<String>,
+
+    /// dotnet executable to use instead of the one in PATH (default: $DOTNET_HOST_PATH or dotnet)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub dotnet: Option<PathBuf>,
+
+    /// Arguments passed on to every dotnet build and dotnet test, e.g. `-- --no-restore -p:Foo=Bar`
+    #[arg(last = true, value_name = "DOTNET_ARGS")]
+    pub extra_args: Vec<String>,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
@@ -21,6 +43,36 @@ pub enum Command {
         /// Filter tests by name pattern
         #[arg(short, long)]
         filter: Option<String>,
+
+        /// Run the tests N times without the TUI and report how flaky each one is
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        repeat: Option<u32>,
+
+        /// With --repeat, stop after the first run with a failing test
+        #[arg(long, requires = "repeat")]
+        until_failure: bool,
+
+        /// Run the tests without the TUI, then re-run failed tests up to N times.
+        /// Tests that pass on a retry are reported as flaky
+        #[arg(long, value_name = "N", conflicts_with = "repeat")]
+        retry_failed: Option<u32>,
+
+        /// With --retry-failed, exit with code 2 when tests only passed on retry
+        #[arg(long, requires = "retry_failed")]
+        fail_on_flaky: bool,
+
+        /// Run the tests without the TUI, collecting code coverage (coverlet), and
+        /// print a coverage summary
+        #[arg(long, conflicts_with = "repeat")]
+        coverage: bool,
+
+        /// With --coverage, exit with code 3 when less than PERCENT of lines are covered
+        #[arg(long, value_name = "PERCENT", requires = "coverage", value_parser = parse_percent)]
+        coverage_threshold: Option<f64>,
+
+        /// Arguments passed on to every dotnet build and dotnet test
+        #[arg(last = true, value_name = "DOTNET_ARGS")]
+        extra_args: Vec<String>,
     },
     /// Run tests changed in a GitHub pull request
     Pr {
@@ -36,10 +88,57 @@ pub enum Command {
         #[arg(long)]
         no_tui: bool,
     },
+    /// Browse results from existing result files without running anything
+    View {
+        /// TRX, xUnit, NUnit3 or JUnit XML files to load (e.g. downloaded CI artifacts)
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Compare two result files and list regressions, fixes and slowdowns
+    #[command(name = "diff-results")]
+    DiffResults {
+        /// Baseline result file (TRX, xUnit, NUnit3 or JUnit XML)
+        #[arg(value_name = "BEFORE")]
+        before: PathBuf,
+
+        /// Result file to compare against the baseline
+        #[arg(value_name = "AFTER")]
+        after: PathBuf,
+    },
+}
+
+fn parse_percent(s: &str) -> Result<f64, String> {
+    match s.trim_end_matches('%').parse::<f64>() {
+        Ok(p) if (0.0..=100.0).contains(&p) => Ok(p),
+        _ => Err(format!("expected a percentage from 0 to 100, got \"{}\"", s)),
+    }
 }
 
 impl Cli {
     pub fn parse_args() -> Self {
         Cli::parse()
     }
+
+    /// Options that override `.testament.toml`
+    pub fn overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            parallel: self.parallel,
+            profile: self.profile.clone(),
+            configuration: self.configuration.clone(),
+            extra_args: self.extra_args.iter().chain(self.run_extra_args()).cloned().collect(),
+            // Commands run in the project directories, so relative paths must be made absolute
+            dotnet: self.dotnet.as_ref().map(|dotnet| match dotnet.components().count() {
+                1 => dotnet.clone(),
+                _ => std::path::absolute(dotnet).unwrap_or_else(|_| dotnet.clone()),
+            }),
+        }
+    }
+
+    /// `testament run ... -- <args>`
+    fn run_extra_args(&self) -> &[String] {
+        match &self.command {
+            Some(Command::Run { extra_args, .. }) => extra_args,
+            _ => &[],
+        }
+    }
 }
@@ -0,0 +1,651 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::error::{Result, TestamentError};
+use crate::parser::ResultFormat;
+
+/// Name of the per-repository config file, looked up in the solution directory.
+pub const CONFIG_FILE_NAME: &str = ".testament.toml";
+
+/// Settings from `.testament.toml`. Every field is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub runner: RunnerConfig,
+    pub watch: WatchConfig,
+    /// Named run profiles, `[profiles.<name>]`
+    pub profiles: BTreeMap<String, RunProfile>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunnerConfig {
+    /// Result logger `dotnet test` writes and testament reads back
+    pub logger: ResultFormat,
+    /// How many projects a solution-wide run tests at once
+    pub parallel: Parallelism,
+    /// When to build a project before running its tests
+    pub build: BuildMode,
+    /// Kill a test run that takes longer than this
+    pub timeout: Option<TimeLimit>,
+    /// Pass `--blame-crash`: record the running tests and dump testhost if it crashes
+    pub blame_crash: bool,
+    /// Pass `--blame-hang --blame-hang-timeout`: dump and kill testhost when a
+    /// single test runs longer than this
+    pub blame_hang_timeout: Option<TimeLimit>,
+    /// How many times a repeated run (`f`) runs the tests (default 10)
+    pub repeat: Option<u32>,
+    /// Collect code coverage with `--collect "XPlat Code Coverage"` (coverlet)
+    pub coverage: bool,
+    /// Percentage of lines a headless coverage run must cover
+    pub coverage_threshold: Option<f64>,
+    /// Run profile to start with, from `[profiles]`
+    pub profile: Option<String>,
+    /// Build configuration for builds, runs and discovery, e.g. `Release`. Takes
+    /// precedence over the profile's.
+    pub configuration: Option<String>,
+    /// Configurations `B` switches between (default Debug and Release)
+    pub configurations: Vec<String>,
+    /// Arguments added to every `dotnet build` and `dotnet test`, e.g. `--no-restore`
+    pub extra_args: Vec<String>,
+    /// dotnet executable to use instead of `dotnet` from PATH
+    pub dotnet: Option<PathBuf>,
+    /// Command started when a debugged test run (`D`) waits for a debugger,
+    /// with `{pid}` replaced by the testhost process id
+    pub debug_attach: Option<String>,
+}
+
+impl RunnerConfig {
+    pub fn repeat_count(&self) -> u32 {
+        self.repeat.unwrap_or(DEFAULT_REPEAT).max(1)
+    }
+}
+
+const DEFAULT_REPEAT: u32 = 10;
+
+/// What `dotnet build` uses without `--configuration`
+const DEFAULT_CONFIGURATION: &str = "Debug";
+
+/// Which file changes trigger a run in watch mode, `[watch]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchConfig {
+    /// Globs, relative to the solution directory, of files that trigger a run
+    pub patterns: Vec<String>,
+    /// Globs of files that never trigger a run, on top of `.gitignore`
+    pub ignore: Vec<String>,
+    /// Skip files ignored by `.gitignore`
+    pub gitignore: bool,
+    /// Quiet time after the last change before running, so editors saving several
+    /// files or a `git checkout` cause a single run
+    pub debounce_ms: u64,
+    /// Tests a change runs in the affected projects (`W` switches)
+    pub target: WatchTarget,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            patterns: vec!["**/*.cs".to_string(), "**/*.csproj".to_string()],
+            ignore: vec!["**/bin/**".to_string(), "**/obj/**".to_string()],
+            gitignore: true,
+            debounce_ms: 500,
+            target: WatchTarget::default(),
+        }
+    }
+}
+
+impl WatchConfig {
+    pub fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce_ms)
+    }
+}
+
+/// Tests watch mode runs in the projects affected by a change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatchTarget {
+    /// Every test of the projects
+    #[default]
+    Project,
+    /// The selected tests, or the class or test under the cursor
+    Selection,
+    /// Tests that failed last time, then the rest
+    FailedFirst,
+    /// Test classes declared in, or named after the classes of, changed files
+    ChangedClasses,
+}
+
+impl WatchTarget {
+    pub fn next(self) -> Self {
+        match self {
+            WatchTarget::Project => WatchTarget::Selection,
+            WatchTarget::Selection => WatchTarget::FailedFirst,
+            WatchTarget::FailedFirst => WatchTarget::ChangedClasses,
+            WatchTarget::ChangedClasses => WatchTarget::Project,
+        }
+    }
+}
+
+impl fmt::Display for WatchTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WatchTarget::Project => "project",
+            WatchTarget::Selection => "selection",
+            WatchTarget::FailedFirst => "failed-first",
+            WatchTarget::ChangedClasses => "changed-classes",
+        })
+    }
+}
+
+/// Settings for one kind of test run, e.g. integration tests that need their own
+/// `.runsettings` and connection strings. Applied to builds and test runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunProfile {
+    /// `.runsettings` file passed as `--settings`, relative to the solution directory
+    pub settings: Option<PathBuf>,
+    /// Environment variables for `dotnet build` and `dotnet test`
+    pub env: BTreeMap<String, String>,
+    /// Build configuration, e.g. `Release`
+    pub configuration: Option<String>,
+    /// Extra `dotnet test` arguments
+    pub args: Vec<String>,
+    /// Additional `--logger` values, e.g. `"html;LogFileName=report.html"`
+    pub loggers: Vec<String>,
+}
+
+/// Settings given on the command line, which take precedence over the config file.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// `--parallel`
+    pub parallel: Option<Parallelism>,
+    /// `--profile`
+    pub profile: Option<String>,
+    /// `--configuration`
+    pub configuration: Option<String>,
+    /// Arguments after `--`, added to `[runner] extra_args`
+    pub extra_args: Vec<String>,
+    /// `--dotnet`
+    pub dotnet: Option<PathBuf>,
+}
+
+impl ConfigOverrides {
+    /// Apply to `config`. Fails for a profile the config doesn't define.
+    pub fn apply(&self, config: &mut Config) -> std::result::Result<(), String> {
+        if let Some(parallel) = self.parallel {
+            config.runner.parallel = parallel;
+        }
+        if let Some(profile) = &self.profile {
+            if !config.profiles.contains_key(profile) {
+                return Err(format!("Profile \"{}\" is not defined in [profiles] of {}", profile, CONFIG_FILE_NAME));
+            }
+            config.runner.profile = Some(profile.clone());
+        }
+        if let Some(configuration) = &self.configuration {
+            config.runner.configuration = Some(configuration.clone());
+        }
+        config.runner.extra_args.extend(self.extra_args.iter().cloned());
+        if let Some(dotnet) = &self.dotnet {
+            config.runner.dotnet = Some(dotnet.clone());
+        }
+        Ok(())
+    }
+}
+
+/// When to build before a test run (runs themselves use `--no-build`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildMode {
+    /// Build before every run
+    Always,
+    /// Build when sources are newer than the test assembly
+    #[default]
+    Stale,
+    /// Never build; `b` builds by hand
+    Never,
+}
+
+/// Number of projects run concurrently: `auto` (CPU cores / 2, minimum 1) or a fixed count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "CountOrName")]
+pub enum Parallelism {
+    #[default]
+    Auto,
+    Fixed(usize),
+}
+
+impl Parallelism {
+    pub fn resolve(self) -> usize {
+        match self {
+            Parallelism::Auto => std::thread::available_parallelism()
+                .map(|n| n.get() / 2)
+                .unwrap_or(1)
+                .max(1),
+            Parallelism::Fixed(n) => n,
+        }
+    }
+}
+
+impl FromStr for Parallelism {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(Parallelism::Auto);
+        }
+        match s.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(Parallelism::Fixed(n)),
+            _ => Err(format!("expected a positive number or \"auto\", got \"{}\"", s)),
+        }
+    }
+}
+
+impl fmt::Display for Parallelism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Parallelism::Auto => write!(f, "auto"),
+            Parallelism::Fixed(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// A length of time: `"90s"`, `"5m"`, `"1h"` or `"500ms"`, or a number of seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLimit(pub Duration);
+
+impl FromStr for TimeLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let duration = match (number.parse::<u64>(), unit) {
+            (Ok(n), "ms") => Some(Duration::from_millis(n)),
+            (Ok(n), "" | "s") => Some(Duration::from_secs(n)),
+            (Ok(n), "m") => n.checked_mul(60).map(Duration::from_secs),
+            (Ok(n), "h") => n.checked_mul(3600).map(Duration::from_secs),
+            _ => None,
+        };
+        let Some(duration) = duration else {
+            return Err(format!("expected a duration such as \"90s\", \"5m\" or \"1h\", got \"{}\"", s));
+        };
+        if duration.is_zero() {
+            return Err(format!("expected a duration greater than zero, got \"{}\"", s));
+        }
+        Ok(TimeLimit(duration))
+    }
+}
+
+impl fmt::Display for TimeLimit {
+    /// Always with a unit, as `dotnet test --blame-hang-timeout` reads a bare number as milliseconds.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = self.0.as_millis();
+        if ms.is_multiple_of(3_600_000) {
+            write!(f, "{}h", ms / 3_600_000)
+        } else if ms.is_multiple_of(60_000) {
+            write!(f, "{}m", ms / 60_000)
+        } else if ms.is_multiple_of(1000) {
+            write!(f, "{}s", ms / 1000)
+        } else {
+            write!(f, "{}ms", ms)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeLimit {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        match CountOrName::deserialize(deserializer)? {
+            CountOrName::Count(n) => n.to_string().parse(),
+            CountOrName::Name(name) => name.parse(),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A setting given as a number or a string, e.g. `parallel = 4` or `parallel = "auto"`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CountOrName {
+    Count(i64),
+    Name(String),
+}
+
+impl TryFrom<CountOrName> for Parallelism {
+    type Error = String;
+
+    fn try_from(value: CountOrName) -> std::result::Result<Self, Self::Error> {
+        match value {
+            CountOrName::Count(n) => n.to_string().parse(),
+            CountOrName::Name(name) => name.parse(),
+        }
+    }
+}
+
+impl Config {
+    /// Load `.testament.toml` from `dir`. A missing file yields the defaults.
+    pub fn load(dir: &Path) -> Result<Config> {
+        let path = dir.join(CONFIG_FILE_NAME);
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let mut config = Config::parse(&content, &path)?;
+                for profile in config.profiles.values_mut() {
+                    if let Some(settings) = profile.settings.as_mut().filter(|s| s.is_relative()) {
+                        *settings = dir.join(&*settings);
+                    }
+                }
+                // A bare name like "dotnet8" is looked up in PATH
+                if let Some(dotnet) = config.runner.dotnet.as_mut().filter(|d| d.is_relative() && d.components().count() > 1) {
+                    *dotnet = dir.join(&*dotnet);
+                }
+                Ok(config)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(TestamentError::FileRead { path, source: e }),
+        }
+    }
+
+    fn parse(content: &str, path: &Path) -> Result<Config> {
+        let config: Config = toml::from_str(content).map_err(|e| TestamentError::InvalidConfig {
+            path: path.to_path_buf(),
+            reason: e.message().to_string(),
+        })?;
+        if let Some(name) = config.runner.profile.as_ref().filter(|name| !config.profiles.contains_key(*name)) {
+            return Err(TestamentError::InvalidConfig {
+                path: path.to_path_buf(),
+                reason: format!("profile \"{}\" is not defined in [profiles]", name),
+            });
+        }
+        for glob in config.watch.patterns.iter().chain(&config.watch.ignore) {
+            if let Err(e) = globset::Glob::new(glob) {
+                return Err(TestamentError::InvalidConfig {
+                    path: path.to_path_buf(),
+                    reason: format!("invalid [watch] glob: {}", e),
+                });
+            }
+        }
+        if let Some(threshold) = config.runner.coverage_threshold {
+            if !(0.0..=100.0).contains(&threshold) {
+                return Err(TestamentError::InvalidConfig {
+                    path: path.to_path_buf(),
+                    reason: format!("coverage_threshold must be a percentage from 0 to 100, got {}", threshold),
+                });
+            }
+        }
+        Ok(config)
+    }
+
+    /// The selected run profile and its name.
+    pub fn active_profile(&self) -> Option<(&str, &RunProfile)> {
+        let name = self.runner.profile.as_deref()?;
+        self.profiles.get_key_value(name).map(|(name, profile)| (name.as_str(), profile))
+    }
+
+    /// Build configuration to use: `[runner] configuration`, else the active
+    /// profile's. None builds the project's default (usually Debug).
+    pub fn configuration(&self) -> Option<&str> {
+        self.runner
+            .configuration
+            .as_deref()
+            .or_else(|| self.active_profile().and_then(|(_, profile)| profile.configuration.as_deref()))
+    }
+
+    /// dotnet executable: `[runner] dotnet`, else `DOTNET_HOST_PATH`, else `dotnet` from PATH.
+    pub fn dotnet_host(&self) -> PathBuf {
+        self.runner
+            .dotnet
+            .clone()
+            .or_else(|| std::env::var_os("DOTNET_HOST_PATH").filter(|p| !p.is_empty()).map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("dotnet"))
+    }
+
+    /// Switch to the next of `[runner] configurations`.
+    pub fn cycle_configuration(&mut self) {
+        let defaults = [DEFAULT_CONFIGURATION.to_string(), "Release".to_string()];
+        let names = if self.runner.configurations.is_empty() { &defaults[..] } else { &self.runner.configurations[..] };
+        let current = self.configuration().unwrap_or(DEFAULT_CONFIGURATION);
+        let next = match names.iter().position(|name| name.eq_ignore_ascii_case(current)) {
+            Some(i) => &names[(i + 1) % names.len()],
+            None => &names[0],
+        };
+        self.runner.configuration = Some(next.clone());
+    }
+
+    /// Select the next profile by name, or none after the last one.
+    pub fn cycle_profile(&mut self) {
+        let names: Vec<&String> = self.profiles.keys().collect();
+        let next = match &self.runner.profile {
+            None => names.first(),
+            Some(current) => names.iter().position(|name| *name == current).and_then(|i| names.get(i + 1)),
+        };
+        self.runner.profile = next.map(|name| name.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_file_uses_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config::load(temp_dir.path()).unwrap();
+        assert_eq!(config.runner.logger, ResultFormat::Trx);
+    }
+
+    #[test]
+    fn test_load_logger() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(CONFIG_FILE_NAME), "[runner]\nlogger = \"junit\"\n").unwrap();
+        let config = Config::load(temp_dir.path()).unwrap();
+        assert_eq!(config.runner.logger, ResultFormat::JUnit);
+    }
+
+    #[test]
+    fn test_load_dotnet_host() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(CONFIG_FILE_NAME), "[runner]\ndotnet = \"tools/fake-dotnet\"\n").unwrap();
+        let config = Config::load(temp_dir.path()).unwrap();
+        assert_eq!(config.dotnet_host(), temp_dir.path().join("tools/fake-dotnet"));
+
+        fs::write(temp_dir.path().join(CONFIG_FILE_NAME), "[runner]\ndotnet = \"dotnet8\"\n").unwrap();
+        let config = Config::load(temp_dir.path()).unwrap();
+        assert_eq!(config.dotnet_host(), PathBuf::from("dotnet8"));
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        let config = Config::parse("", Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.runner.logger, ResultFormat::Trx);
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        let config = Config::parse("[watch]\npatterns = [\"src/**/*.cs\"]\ndebounce_ms = 200\n", Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.watch.patterns, vec!["src/**/*.cs"]);
+        assert_eq!(config.watch.ignore, vec!["**/bin/**", "**/obj/**"]);
+        assert!(config.watch.gitignore);
+        assert_eq!(config.watch.debounce(), Duration::from_millis(200));
+        assert_eq!(config.watch.target, WatchTarget::Project);
+
+        let config = Config::parse("[watch]\ntarget = \"failed-first\"\n", Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.watch.target, WatchTarget::FailedFirst);
+        assert_eq!(config.watch.target.next().to_string(), "changed-classes");
+
+        let result = Config::parse("[watch]\nignore = [\"[bin\"]\n", Path::new(CONFIG_FILE_NAME));
+        assert!(matches!(result, Err(TestamentError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_parse_parallel() {
+        let config = Config::parse("[runner]\nparallel = 4\n", Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.runner.parallel, Parallelism::Fixed(4));
+
+        let config = Config::parse("[runner]\nparallel = \"auto\"\n", Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.runner.parallel, Parallelism::Auto);
+
+        let result = Config::parse("[runner]\nparallel = 0\n", Path::new(CONFIG_FILE_NAME));
+        assert!(matches!(result, Err(TestamentError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_parse_build_mode() {
+        let config = Config::parse("", Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.runner.build, BuildMode::Stale);
+
+        let config = Config::parse("[runner]\nbuild = \"never\"\n", Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.runner.build, BuildMode::Never);
+
+        let result = Config::parse("[runner]\nbuild = \"sometimes\"\n", Path::new(CONFIG_FILE_NAME));
+        assert!(matches!(result, Err(TestamentError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_parse_timeouts_and_blame() {
+        let config = Config::parse(
+            "[runner]\ntimeout = \"30m\"\nblame_crash = true\nblame_hang_timeout = 90\n",
+            Path::new(CONFIG_FILE_NAME),
+        )
+        .unwrap();
+        assert_eq!(config.runner.timeout, Some(TimeLimit(Duration::from_secs(1800))));
+        assert!(config.runner.blame_crash);
+        assert_eq!(config.runner.blame_hang_timeout, Some(TimeLimit(Duration::from_secs(90))));
+        assert_eq!(config.runner.repeat_count(), DEFAULT_REPEAT);
+
+        let result = Config::parse("[runner]\ntimeout = \"soon\"\n", Path::new(CONFIG_FILE_NAME));
+        assert!(matches!(result, Err(TestamentError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_parse_coverage() {
+        let config = Config::parse("[runner]\ncoverage = true\ncoverage_threshold = 80\n", Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert!(config.runner.coverage);
+        assert_eq!(config.runner.coverage_threshold, Some(80.0));
+
+        let result = Config::parse("[runner]\ncoverage_threshold = 120\n", Path::new(CONFIG_FILE_NAME));
+        assert!(matches!(result, Err(TestamentError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join(CONFIG_FILE_NAME),
+            r#"[runner]
+profile = "integration"
+
+[profiles.integration]
+settings = "integration.runsettings"
+configuration = "Release"
+args = ["--blame"]
+loggers = ["html"]
+env = { ConnectionStrings__Db = "Server=localhost" }
+
+[profiles.unit]
+"#,
+        )
+        .unwrap();
+
+        let mut config = Config::load(temp_dir.path()).unwrap();
+        let (name, profile) = config.active_profile().unwrap();
+        assert_eq!(name, "integration");
+        assert_eq!(profile.settings.as_deref(), Some(temp_dir.path().join("integration.runsettings").as_path()));
+        assert_eq!(profile.configuration.as_deref(), Some("Release"));
+        assert_eq!(profile.env["ConnectionStrings__Db"], "Server=localhost");
+
+        config.cycle_profile();
+        assert_eq!(config.active_profile().map(|(name, _)| name), Some("unit"));
+        config.cycle_profile();
+        assert!(config.active_profile().is_none());
+        config.cycle_profile();
+        assert_eq!(config.active_profile().map(|(name, _)| name), Some("integration"));
+    }
+
+    #[test]
+    fn test_cycle_configuration() {
+        let mut config = Config::default();
+        assert_eq!(config.configuration(), None);
+        config.cycle_configuration();
+        assert_eq!(config.configuration(), Some("Release"));
+        config.cycle_configuration();
+        assert_eq!(config.configuration(), Some("Debug"));
+
+        let mut config = Config::parse(
+            "[runner]\nconfigurations = [\"Debug\", \"Release\", \"Staging\"]\nprofile = \"perf\"\n\n[profiles.perf]\nconfiguration = \"Release\"\n",
+            Path::new(CONFIG_FILE_NAME),
+        )
+        .unwrap();
+        assert_eq!(config.configuration(), Some("Release"));
+        config.cycle_configuration();
+        assert_eq!(config.configuration(), Some("Staging"));
+    }
+
+    #[test]
+    fn test_extra_args_override_appends() {
+        let mut config = Config::parse("[runner]\nextra_args = [\"--no-restore\"]\n", Path::new(CONFIG_FILE_NAME)).unwrap();
+        let overrides = ConfigOverrides { extra_args: vec!["-p:Foo=Bar".to_string()], ..Default::default() };
+        overrides.apply(&mut config).unwrap();
+        assert_eq!(config.runner.extra_args, vec!["--no-restore", "-p:Foo=Bar"]);
+    }
+
+    #[test]
+    fn test_parse_unknown_profile() {
+        let result = Config::parse("[runner]\nprofile = \"e2e\"\n", Path::new(CONFIG_FILE_NAME));
+        assert!(matches!(result, Err(TestamentError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_time_limit_round_trip() {
+        for (input, shown) in [("90s", "90s"), ("120s", "2m"), ("1h", "1h"), ("1500ms", "1500ms"), ("45", "45s")] {
+            assert_eq!(input.parse::<TimeLimit>().unwrap().to_string(), shown);
+        }
+        assert!("0s".parse::<TimeLimit>().is_err());
+        assert!("5d".parse::<TimeLimit>().is_err());
+        assert!("".parse::<TimeLimit>().is_err());
+    }
+
+    #[test]
+    fn test_time_limit_overflow() {
+        assert!(format!("{}h", u64::MAX).parse::<TimeLimit>().unwrap_err().starts_with("expected a duration such as"));
+        assert!(format!("{}m", u64::MAX / 10).parse::<TimeLimit>().is_err());
+        assert!(format!("{}s", u64::MAX).parse::<TimeLimit>().is_ok());
+    }
+
+    #[test]
+    fn test_parallelism_from_str() {
+        assert_eq!("auto".parse::<Parallelism>(), Ok(Parallelism::Auto));
+        assert_eq!("2".parse::<Parallelism>(), Ok(Parallelism::Fixed(2)));
+        assert!("0".parse::<Parallelism>().is_err());
+        assert!("many".parse::<Parallelism>().is_err());
+    }
+
+    #[test]
+    fn test_parallelism_resolve() {
+        assert_eq!(Parallelism::Fixed(3).resolve(), 3);
+        assert!(Parallelism::Auto.resolve() >= 1);
+    }
+
+    #[test]
+    fn test_parse_unknown_logger() {
+        let result = Config::parse("[runner]\nlogger = \"html\"\n", Path::new(CONFIG_FILE_NAME));
+        assert!(matches!(result, Err(TestamentError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn test_parse_unknown_key() {
+        let result = Config::parse("[runner]\nlogegr = \"trx\"\n", Path::new(CONFIG_FILE_NAME));
+        match result {
+            Err(TestamentError::InvalidConfig { reason, .. }) => assert!(reason.contains("logegr")),
+            other => panic!("Expected InvalidConfig, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::process::{Command, Stdio};
+
+/// Command attaching a debugger to `pid`, from a `[runner] debug_attach` setting
+/// such as `tmux split-window netcoredbg --attach {pid}`. None for an empty setting.
+pub fn attach_command(template: &str, pid: u32) -> Option<Command> {
+    let (program, args) = attach_args(template, pid)?;
+    let mut cmd = Command::new(program);
+    // The TUI owns the terminal; the debugger opens its own window or pane
+    cmd.args(args).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    Some(cmd)
+}
+
+/// Split the setting on whitespace and put the process id in place of `{pid}`.
+fn attach_args(template: &str, pid: u32) -> Option<(String, Vec<String>)> {
+    let mut parts = template.split_whitespace().map(|part| part.replace("{pid}", &pid.to_string()));
+    let program = parts.next()?;
+    Some((program, parts.collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attach_args() {
+        assert_eq!(
+            attach_args("tmux split-window netcoredbg --attach {pid}", 4242),
+            Some((
+                "tmux".to_string(),
+                vec!["split-window".to_string(), "netcoredbg".to_string(), "--attach".to_string(), "4242".to_string()]
+            ))
+        );
+        assert_eq!(attach_args("  ", 1), None);
+    }
+}
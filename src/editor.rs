@@ -0,0 +1,64 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Command opening `file` at `line` and `column` in the user's editor: `$VISUAL`,
+/// then `$EDITOR`, falling back to `vi` (`notepad` on Windows).
+pub fn editor_command(file: &Path, line: u32, column: u32) -> Command {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+
+    let (program, args) = editor_args(&editor, &file.to_string_lossy(), line, column);
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    cmd
+}
+
+/// Split an editor setting such as `code --wait` and add its go-to-line arguments.
+fn editor_args(editor: &str, file: &str, line: u32, column: u32) -> (String, Vec<String>) {
+    let mut parts = editor.split_whitespace().map(str::to_string);
+    let program = parts.next().unwrap_or_default();
+    let mut args: Vec<String> = parts.collect();
+
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(&program).to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    match name {
+        "code" | "code-insiders" | "codium" | "cursor" => {
+            args.extend(["-g".to_string(), format!("{}:{}:{}", file, line, column)]);
+        }
+        "subl" | "zed" | "hx" | "helix" => args.push(format!("{}:{}:{}", file, line, column)),
+        "notepad++" => args.extend([format!("-n{}", line), format!("-c{}", column), file.to_string()]),
+        // No way to jump to a line
+        "notepad" => args.push(file.to_string()),
+        // vi, vim, nvim, nano, emacs, micro, kak, ...
+        _ => args.extend([format!("+{}", line), file.to_string()]),
+    }
+    (program, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_args_line_flag() {
+        assert_eq!(
+            editor_args("nvim", "/src/A.cs", 12, 5),
+            ("nvim".to_string(), vec!["+12".to_string(), "/src/A.cs".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_editor_args_goto() {
+        assert_eq!(
+            editor_args("code --wait", "/src/A.cs", 12, 5),
+            ("code".to_string(), vec!["--wait".to_string(), "-g".to_string(), "/src/A.cs:12:5".to_string()])
+        );
+        assert_eq!(
+            editor_args(r"C:\Tools\Code.exe", r"C:\src\A.cs", 3, 1).1,
+            ["-g", r"C:\src\A.cs:3:1"]
+        );
+    }
+}
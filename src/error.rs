@@ -19,6 +19,12 @@ pub enum TestamentError {
     #[error("Failed to parse TRX file: {0}")]
     TrxParse(String),
 
+    #[error("Failed to parse test results: {0}")]
+    ResultParse(String),
+
+    #[error("Invalid config file {path}: {reason}")]
+    InvalidConfig { path: PathBuf, reason: String },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -124,6 +130,46 @@ mod tests {
         assert!(debug_str.contains("Malformed XML"));
     }
 
+    // ResultParse tests
+    #[test]
+    fn test_result_parse_display() {
+        let error = TestamentError::ResultParse("JUnit XML parse error".to_string());
+        assert_eq!(
+            format!("{}", error),
+            "Failed to parse test results: JUnit XML parse error"
+        );
+    }
+
+    #[test]
+    fn test_result_parse_debug() {
+        let error = TestamentError::ResultParse("bad".to_string());
+        assert!(format!("{:?}", error).contains("ResultParse"));
+    }
+
+    // InvalidConfig tests
+    #[test]
+    fn test_invalid_config_display() {
+        let error = TestamentError::InvalidConfig {
+            path: PathBuf::from("/repo/.testament.toml"),
+            reason: "unknown field `foo`".to_string(),
+        };
+        assert_eq!(
+            format!("{}", error),
+            "Invalid config file /repo/.testament.toml: unknown field `foo`"
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_debug() {
+        let error = TestamentError::InvalidConfig {
+            path: PathBuf::from(".testament.toml"),
+            reason: "bad".to_string(),
+        };
+        let debug_str = format!("{:?}", error);
+        assert!(debug_str.contains("InvalidConfig"));
+        assert!(debug_str.contains(".testament.toml"));
+    }
+
     // Io tests
     #[test]
     fn test_io_display() {
@@ -154,6 +200,7 @@ mod tests {
 
     // Result type alias tests
     #[test]
+    #[allow(clippy::unnecessary_literal_unwrap)]
     fn test_result_ok() {
         let result: Result<i32> = Ok(42);
         assert!(result.is_ok());
@@ -196,7 +243,12 @@ mod tests {
             TestamentError::NoSolutionFound,
             TestamentError::DotnetExecution("test".to_string()),
             TestamentError::TrxParse("test".to_string()),
-            TestamentError::Io(IoError::new(ErrorKind::Other, "test")),
+            TestamentError::ResultParse("test".to_string()),
+            TestamentError::InvalidConfig {
+                path: PathBuf::from("test"),
+                reason: "test".to_string(),
+            },
+            TestamentError::Io(IoError::other("test")),
         ];
 
         for error in errors {
@@ -115,7 +115,6 @@ pub struct ChangedTest {
     pub file_path: String,
     pub class_name: String,
     pub method_name: String,
-    #[allow(dead_code)]
     pub full_name: String,
 }
 
@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+
+use crate::config::Config;
+use crate::model::{OutputSource, RepeatStats, TestProject, TestStatus};
+use crate::parser::{parse_diagnostic, CoverageReport, TestOutcome, TestReport, TestResult};
+use crate::runner::{DiscoveryEvent, ExecutorEvent, TestExecutor};
+
+/// A project to run without the TUI: all of its tests, or only `tests`.
+#[derive(Clone)]
+pub struct Target {
+    pub name: String,
+    pub path: PathBuf,
+    pub tests: Option<Vec<String>>,
+}
+
+/// Wait for discovery, then pick what to run: tests whose fully-qualified name
+/// contains `filter` (case-insensitive), or every project.
+pub fn select_targets(
+    mut projects: Vec<TestProject>,
+    discovery_rx: Receiver<DiscoveryEvent>,
+    filter: Option<&str>,
+) -> Vec<Target> {
+    for event in discovery_rx {
+        match event {
+            DiscoveryEvent::ProjectDiscovered(idx, classes) => {
+                if let Some(project) = projects.get_mut(idx) {
+                    project.classes = classes;
+                }
+            }
+            DiscoveryEvent::ProjectError(idx, error) => {
+                let name = projects.get(idx).map(|p| p.name.as_str()).unwrap_or_default();
+                let first_line = error.lines().next().unwrap_or_default();
+                eprintln!("[Discovery] {} failed: {}", name, first_line);
+            }
+            DiscoveryEvent::Complete => break,
+        }
+    }
+
+    let filter = filter.map(str::to_lowercase);
+    projects
+        .into_iter()
+        .filter_map(|project| {
+            let tests = match &filter {
+                Some(filter) => {
+                    let tests: Vec<String> = project.classes.iter()
+                        .flat_map(|c| &c.tests)
+                        .filter(|t| t.full_name.to_lowercase().contains(filter))
+                        .map(|t| t.full_name.clone())
+                        .collect();
+                    if tests.is_empty() {
+                        return None;
+                    }
+                    Some(tests)
+                }
+                None => None,
+            };
+            Some(Target { name: project.name, path: project.path, tests })
+        })
+        .collect()
+}
+
+/// Run one project to completion, printing build errors, stderr and testament's
+/// own messages as they arrive. Returns the results, and the coverage when the
+/// runner collects it.
+pub fn run_target(target: &Target, config: &Config) -> Result<(TestReport, Option<CoverageReport>), String> {
+    let handle = TestExecutor::configured(&target.path, config).run(target.tests.clone());
+    let mut coverage = None;
+    while let Ok(event) = handle.recv() {
+        match event {
+            ExecutorEvent::BuildStarted => println!("  Building {}...", target.name),
+            ExecutorEvent::BuildOutput(line)
+                if line.source == OutputSource::Stderr || parse_diagnostic(&line.content).is_some() =>
+            {
+                eprintln!("  {}", line.content);
+            }
+            ExecutorEvent::OutputLine(line) if line.source != OutputSource::Stdout => {
+                eprintln!("  {}", line.content);
+            }
+            ExecutorEvent::Coverage(report) => coverage = Some(report),
+            ExecutorEvent::Completed(report) => return Ok((report, coverage)),
+            ExecutorEvent::Error(e) => return Err(e),
+            ExecutorEvent::Cancelled => return Err("Cancelled.".to_string()),
+            _ => {}
+        }
+    }
+    Err("The test run ended without results.".to_string())
+}
+
+/// Run the targets `count` times, or until the first run with a failure, and
+/// print each test's pass/fail counts and duration spread. Returns the exit
+/// code: 1 when any test failed in any run.
+pub fn run_repeat(targets: &[Target], config: &Config, count: u32, until_failure: bool) -> i32 {
+    let mut stats: BTreeMap<String, RepeatStats> = BTreeMap::new();
+    let mut completed = 0;
+    for run in 1..=count {
+        println!("\nRun {}/{}", run, count);
+        let mut failed = 0;
+        for target in targets {
+            let report = match run_target(target, config) {
+                Ok((report, _)) => report,
+                Err(e) => {
+                    eprintln!("  {}: {}", target.name, e);
+                    return 1;
+                }
+            };
+            for result in &report.results {
+                let name = result_name(&report, result);
+                let status = match result.outcome {
+                    TestOutcome::Passed => TestStatus::Passed,
+                    TestOutcome::Failed => TestStatus::Failed,
+                    TestOutcome::Skipped => TestStatus::Skipped,
+                };
+                failed += usize::from(status == TestStatus::Failed);
+                stats.entry(name).or_default().record(&status, Some(result.duration_ms));
+            }
+            println!("  {}: {} test(s)", target.name, report.results.len());
+        }
+        completed = run;
+        if failed > 0 {
+            println!("  {} failed", failed);
+            if until_failure {
+                break;
+            }
+        }
+    }
+
+    print_repeat_summary(&stats, completed);
+    i32::from(stats.values().any(|s| s.failed > 0))
+}
+
+/// Exit code when tests only passed on retry and `--fail-on-flaky` is given.
+pub const EXIT_FLAKY: i32 = 2;
+
+/// Exit code when the tests pass but cover less than the coverage threshold.
+pub const EXIT_COVERAGE: i32 = 3;
+
+/// Run the targets once, then re-run only the failed tests (as `a` does in the
+/// TUI) up to `retries` times. Tests that pass on a retry are reported as flaky.
+/// With coverage collected, its summary follows and is checked against
+/// `coverage_threshold` (percentage of lines).
+///
+/// Returns the exit code: 1 when tests still fail, `EXIT_COVERAGE` when coverage
+/// is below the threshold, `EXIT_FLAKY` when some tests were flaky and
+/// `fail_on_flaky` is set, 0 otherwise.
+pub fn run_with_retries(
+    targets: &[Target],
+    config: &Config,
+    retries: u32,
+    fail_on_flaky: bool,
+    coverage_threshold: Option<f64>,
+) -> i32 {
+    let mut passed = 0;
+    // Per target: failed test name -> first line of its error
+    let mut failing: Vec<BTreeMap<String, String>> = Vec::new();
+    // Coverage of the first, complete run; retries only run the failed tests
+    let mut coverage: Option<CoverageReport> = None;
+    for target in targets {
+        let report = match run_target(target, config) {
+            Ok((report, target_coverage)) => {
+                if let Some(target_coverage) = target_coverage {
+                    coverage.get_or_insert_with(CoverageReport::default).merge(target_coverage);
+                }
+                report
+            }
+            Err(e) => {
+                eprintln!("  {}: {}", target.name, e);
+                return 1;
+            }
+        };
+        let mut failed = BTreeMap::new();
+        for result in &report.results {
+            match result.outcome {
+                TestOutcome::Passed => passed += 1,
+                TestOutcome::Failed => {
+                    failed.insert(result_name(&report, result), first_line(result.error_message.as_deref()));
+                }
+                TestOutcome::Skipped => {}
+            }
+        }
+        println!("  {}: {} test(s), {} failed", target.name, report.results.len(), failed.len());
+        failing.push(failed);
+    }
+
+    let mut flaky: Vec<(String, u32)> = Vec::new();
+    for attempt in 1..=retries {
+        let remaining: usize = failing.iter().map(BTreeMap::len).sum();
+        if remaining == 0 {
+            break;
+        }
+        println!("\nRetry {}/{}: {} failed test(s)", attempt, retries, remaining);
+        for (target, failed) in targets.iter().zip(failing.iter_mut()) {
+            if failed.is_empty() {
+                continue;
+            }
+            let retry = Target { tests: Some(failed.keys().cloned().collect()), ..target.clone() };
+            let report = match run_target(&retry, config) {
+                Ok((report, _)) => report,
+                Err(e) => {
+                    eprintln!("  {}: {}", target.name, e);
+                    continue;
+                }
+            };
+            for result in &report.results {
+                let name = result_name(&report, result);
+                match result.outcome {
+                    TestOutcome::Passed if failed.remove(&name).is_some() => flaky.push((name, attempt)),
+                    TestOutcome::Failed if failed.contains_key(&name) => {
+                        failed.insert(name, first_line(result.error_message.as_deref()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let failed: Vec<(String, String)> = failing.into_iter().flatten().collect();
+    println!("\n========================================");
+    println!("{} passed, {} flaky, {} failed", passed, flaky.len(), failed.len());
+    if !flaky.is_empty() {
+        println!("\nFlaky ({}):", flaky.len());
+        for (name, attempt) in &flaky {
+            println!("  {} - passed on retry {}", name, attempt);
+        }
+    }
+    if !failed.is_empty() {
+        println!("\nFailed ({}):", failed.len());
+        for (name, error) in &failed {
+            println!("  {} - {}", name, error);
+        }
+    }
+
+    let below_threshold = match (&coverage, config.runner.coverage) {
+        (Some(coverage), _) => print_coverage_summary(coverage, coverage_threshold),
+        (None, true) => {
+            println!("\nNo coverage was collected.");
+            coverage_threshold.is_some()
+        }
+        (None, false) => false,
+    };
+
+    if !failed.is_empty() {
+        1
+    } else if below_threshold {
+        EXIT_COVERAGE
+    } else if fail_on_flaky && !flaky.is_empty() {
+        EXIT_FLAKY
+    } else {
+        0
+    }
+}
+
+/// Fully-qualified name of a result, as the retry filter needs it.
+fn result_name(report: &TestReport, result: &TestResult) -> String {
+    report.qualified_name(result).unwrap_or_else(|| result.test_name.clone())
+}
+
+fn first_line(message: Option<&str>) -> String {
+    message.and_then(|m| m.lines().next()).unwrap_or_default().trim().to_string()
+}
+
+/// Print total, per-assembly and per-namespace coverage. Returns whether line coverage is
+/// below `threshold`.
+fn print_coverage_summary(coverage: &CoverageReport, threshold: Option<f64>) -> bool {
+    let totals = coverage.totals();
+    println!("\nCoverage: {}", totals.summary());
+    for (title, groups) in [("Assemblies", coverage.by_assembly()), ("Namespaces", coverage.by_namespace())] {
+        println!("\n{}:", title);
+        for (name, counts) in groups {
+            println!("  {} - {}", if name.is_empty() { "(global)" } else { &name }, counts.summary());
+        }
+    }
+    let Some(threshold) = threshold else { return false };
+    let line_percent = totals.line_percent().unwrap_or(0.0);
+    if line_percent < threshold {
+        println!("\nLine coverage {:.1}% is below the threshold of {}%", line_percent, threshold);
+        true
+    } else {
+        println!("\nLine coverage {:.1}% meets the threshold of {}%", line_percent, threshold);
+        false
+    }
+}
+
+fn print_repeat_summary(stats: &BTreeMap<String, RepeatStats>, runs: u32) {
+    let flaky: Vec<_> = stats.iter().filter(|(_, s)| s.is_flaky()).collect();
+    let broken: Vec<_> = stats.iter().filter(|(_, s)| s.failed > 0 && !s.is_flaky()).collect();
+
+    println!("\n========================================");
+    println!("{} run(s) of {} test(s)", runs, stats.len());
+    for (title, tests) in [("Flaky", &flaky), ("Failed every run", &broken)] {
+        if tests.is_empty() {
+            continue;
+        }
+        println!("\n{} ({}):", title, tests.len());
+        for (name, s) in tests {
+            let spread = s
+                .duration_spread()
+                .map(|(min, median, max)| format!(" (min {} ms, median {} ms, max {} ms)", min, median, max))
+                .unwrap_or_default();
+            println!("  {} - {}{}", name, s.summary(), spread);
+        }
+    }
+    if flaky.is_empty() && broken.is_empty() {
+        println!("\nNo failures.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Test, TestClass};
+    use std::sync::mpsc;
+
+    fn project_with_tests(name: &str, tests: &[&str]) -> Vec<TestClass> {
+        let mut class = TestClass::new("UserTests".to_string(), name.to_string());
+        for test in tests {
+            class.tests.push(Test::new(test.to_string(), format!("{}.UserTests.{}", name, test)));
+        }
+        vec![class]
+    }
+
+    #[test]
+    fn test_select_targets_filters_by_name() {
+        let projects = vec![
+            TestProject::new("Api.Tests".to_string(), PathBuf::from("/src/Api.Tests/Api.Tests.csproj")),
+            TestProject::new("Web.Tests".to_string(), PathBuf::from("/src/Web.Tests/Web.Tests.csproj")),
+        ];
+        let (tx, rx) = mpsc::channel();
+        tx.send(DiscoveryEvent::ProjectDiscovered(0, project_with_tests("Api", &["Login", "Logout"]))).unwrap();
+        tx.send(DiscoveryEvent::ProjectDiscovered(1, project_with_tests("Web", &["Render"]))).unwrap();
+        tx.send(DiscoveryEvent::Complete).unwrap();
+
+        let targets = select_targets(projects, rx, Some("login"));
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].name, "Api.Tests");
+        assert_eq!(targets[0].tests.as_deref(), Some(&["Api.UserTests.Login".to_string()][..]));
+    }
+
+    #[test]
+    fn test_select_targets_without_filter_runs_every_project() {
+        let projects = vec![TestProject::new("Api.Tests".to_string(), PathBuf::from("/Api.Tests.csproj"))];
+        let (tx, rx) = mpsc::channel();
+        tx.send(DiscoveryEvent::Complete).unwrap();
+
+        let targets = select_targets(projects, rx, None);
+        assert_eq!(targets.len(), 1);
+        assert!(targets[0].tests.is_none());
+    }
+}
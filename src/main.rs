@@ -1,37 +1,97 @@
 mod app;
 mod cli;
+mod config;
+mod debugger;
+mod editor;
 mod error;
 mod git;
+mod headless;
 mod model;
 mod parser;
 mod runner;
 mod ui;
 
 use std::env;
-use std::process::Command;
 
 use cli::{Cli, Command as CliCommand};
+use model::{diff_runs, summarize, ChangeKind};
 use git::{extract_changed_tests, fetch_pr_diff, get_github_token, parse_pr_url};
-use runner::{discover_projects_lazy, discover_projects_from_paths, find_solution, find_csproj_in_dir};
+use parser::parse_test_file;
+use runner::{discover_projects_lazy, discover_projects_from_paths, find_solution, find_csproj_for_file, find_csproj_in_dir, load_result_projects, load_snapshot, methods_filters};
 
 fn main() {
     let cli = Cli::parse_args();
+    let overrides = cli.overrides();
 
-    // Handle PR subcommand
-    if let Some(CliCommand::Pr { url, path, no_tui }) = cli.command {
-        run_pr_mode(&url, path, no_tui);
-        return;
+    match cli.command {
+        // Handle PR subcommand
+        Some(CliCommand::Pr { url, path, no_tui }) => {
+            run_pr_mode(&url, path, no_tui, overrides);
+            return;
+        }
+        Some(CliCommand::View { files }) => {
+            run_view_mode(&files);
+            return;
+        }
+        Some(CliCommand::DiffResults { before, after }) => {
+            run_diff_mode(&before, &after);
+            return;
+        }
+        _ => {}
     }
 
-    // Normal TUI mode
     let start_dir = cli.path.unwrap_or_else(|| env::current_dir().unwrap());
 
+    match cli.command {
+        Some(CliCommand::Run { filter, repeat: Some(count), until_failure, .. }) => {
+            let (targets, config) = headless_targets(&start_dir, filter.as_deref(), &overrides);
+            std::process::exit(headless::run_repeat(&targets, &config, count, until_failure));
+        }
+        Some(CliCommand::Run { filter, retry_failed, fail_on_flaky, coverage, coverage_threshold, .. })
+            if retry_failed.is_some() || coverage =>
+        {
+            let (targets, mut config) = headless_targets(&start_dir, filter.as_deref(), &overrides);
+            config.runner.coverage |= coverage;
+            let threshold = coverage_threshold.or(config.runner.coverage_threshold);
+            let retries = retry_failed.unwrap_or(0);
+            std::process::exit(headless::run_with_retries(&targets, &config, retries, fail_on_flaky, threshold));
+        }
+        _ => {}
+    }
+
+    // Normal TUI mode
+    let (projects, discovery_rx, solution_dir, context) = discover(&start_dir, &overrides);
+    if let Err(e) = app::run(projects, solution_dir, discovery_rx, context, overrides) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Find the solution or projects under `start_dir` and start discovering their
+/// tests. Returns the projects, discovery events, solution directory and header context.
+fn discover(
+    start_dir: &std::path::Path,
+    overrides: &config::ConfigOverrides,
+) -> (Vec<model::TestProject>, std::sync::mpsc::Receiver<runner::DiscoveryEvent>, std::path::PathBuf, Option<String>) {
+    let start_dir = start_dir.to_path_buf();
     let sln_path = find_solution(&start_dir).ok();
 
+    let solution_dir = if let Some(ref sln) = sln_path {
+        if sln.extension().is_some_and(|ext| ext == "sln") {
+            sln.parent().unwrap_or(&start_dir).to_path_buf()
+        } else {
+            start_dir.clone()
+        }
+    } else {
+        start_dir.clone()
+    };
+    let startup = startup_config(&solution_dir, overrides);
+    let (configuration, extra_args) = (startup.configuration(), &startup.runner.extra_args[..]);
+
     let (projects, discovery_rx) = if let Some(ref sln) = sln_path {
         if sln.extension().is_some_and(|ext| ext == "sln") {
             // Found .sln - parse it for test projects
-            match discover_projects_lazy(sln) {
+            match discover_projects_lazy(sln, configuration, extra_args) {
                 Ok(result) => result,
                 Err(e) => {
                     eprintln!("Failed to discover projects: {}", e);
@@ -41,7 +101,7 @@ fn main() {
         } else {
             // find_solution returned a .csproj - find all csproj files in directory instead
             let csproj_files = find_csproj_in_dir(&start_dir).unwrap_or_else(|_| vec![sln.clone()]);
-            match discover_projects_from_paths(csproj_files) {
+            match discover_projects_from_paths(csproj_files, configuration, extra_args) {
                 Ok(result) => result,
                 Err(e) => {
                     eprintln!("Failed to discover projects: {}", e);
@@ -52,7 +112,7 @@ fn main() {
     } else {
         // find_solution failed - try recursive csproj search in directory
         match find_csproj_in_dir(&start_dir) {
-            Ok(csproj_files) => match discover_projects_from_paths(csproj_files) {
+            Ok(csproj_files) => match discover_projects_from_paths(csproj_files, configuration, extra_args) {
                 Ok(result) => result,
                 Err(e) => {
                     eprintln!("Failed to discover projects: {}", e);
@@ -66,16 +126,6 @@ fn main() {
         }
     };
 
-    let solution_dir = if let Some(ref sln) = sln_path {
-        if sln.extension().is_some_and(|ext| ext == "sln") {
-            sln.parent().unwrap_or(&start_dir).to_path_buf()
-        } else {
-            start_dir.clone()
-        }
-    } else {
-        start_dir.clone()
-    };
-
     // Build context string from solution/project name
     let context = if let Some(ref sln) = sln_path {
         if sln.extension().is_some_and(|ext| ext == "sln") {
@@ -92,14 +142,94 @@ fn main() {
             .and_then(|n| n.to_str())
             .map(|name| format!("Running Tests in: {}", name))
     };
+    let context = match (context, sdk_summary(&solution_dir)) {
+        (Some(context), Some(sdk)) => Some(format!("{}  |  {}", context, sdk)),
+        (context, sdk) => context.or(sdk),
+    };
+
+    (projects, discovery_rx, solution_dir, context)
+}
+
+/// Config tests are discovered with (build configuration and extra arguments).
+/// Also selects the dotnet host every command uses. Config errors are reported
+/// later, when the config is loaded for running.
+fn startup_config(solution_dir: &std::path::Path, overrides: &config::ConfigOverrides) -> config::Config {
+    let mut config = config::Config::load(solution_dir).unwrap_or_default();
+    let _ = overrides.apply(&mut config);
+    runner::set_dotnet_host(config.dotnet_host());
+    config
+}
+
+/// The SDK pinned by global.json for the header. Warns on stderr when it isn't installed.
+fn sdk_summary(solution_dir: &std::path::Path) -> Option<String> {
+    match runner::sdk_status(solution_dir) {
+        Ok(Some(status)) => {
+            if let Some(warning) = status.warning() {
+                eprintln!("[SDK] {}", warning);
+                return Some(format!("{} (not installed)", status.summary()));
+            }
+            Some(status.summary())
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("[SDK] {}", e);
+            None
+        }
+    }
+}
+
+/// Projects and tests to run without the TUI (`testament run --repeat`,
+/// `--retry-failed` or `--coverage`), and the config to run them with.
+fn headless_targets(
+    start_dir: &std::path::Path,
+    filter: Option<&str>,
+    overrides: &config::ConfigOverrides,
+) -> (Vec<headless::Target>, config::Config) {
+    let (projects, discovery_rx, solution_dir, _) = discover(start_dir, overrides);
+    let mut config = config::Config::load(&solution_dir).unwrap_or_else(|e| {
+        eprintln!("[Config] {} (using defaults)", e);
+        config::Config::default()
+    });
+    if let Err(e) = overrides.apply(&mut config) {
+        eprintln!("[Config] {}", e);
+        std::process::exit(1);
+    }
+    if let Some((name, _)) = config.active_profile() {
+        println!("Profile: {}", name);
+    }
+
+    let targets = headless::select_targets(projects, discovery_rx, filter);
+    if targets.is_empty() {
+        eprintln!("No tests match the filter.");
+        std::process::exit(1);
+    }
+    (targets, config)
+}
+
+fn run_view_mode(files: &[std::path::PathBuf]) {
+    let projects = match load_result_projects(files) {
+        Ok(projects) => projects,
+        Err(e) => {
+            eprintln!("Failed to load results: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let context = match files {
+        [file] => file.file_name()
+            .and_then(|n| n.to_str())
+            .map(|name| format!("Viewing Results: {}", name)),
+        _ => Some(format!("Viewing Results: {} files", files.len())),
+    };
 
-    if let Err(e) = app::run(projects, solution_dir, discovery_rx, context) {
+    let solution_dir = env::current_dir().unwrap();
+    if let Err(e) = app::run_view(projects, solution_dir, context) {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     }
 }
 
-fn run_pr_mode(url: &str, path: Option<std::path::PathBuf>, no_tui: bool) {
+fn run_pr_mode(url: &str, path: Option<std::path::PathBuf>, no_tui: bool, overrides: config::ConfigOverrides) {
     println!("Fetching PR: {}", url);
 
     // Parse PR URL
@@ -160,6 +290,14 @@ fn run_pr_mode(url: &str, path: Option<std::path::PathBuf>, no_tui: bool) {
         std::process::exit(1);
     }
 
+    let start_dir = path.unwrap_or_else(|| env::current_dir().unwrap());
+    // Use the first project's parent or start_dir as solution_dir
+    let solution_dir = project_paths.first()
+        .and_then(|p| p.parent())
+        .map(|p| p.to_path_buf())
+        .unwrap_or(start_dir);
+    let startup = startup_config(&solution_dir, &overrides);
+
     if no_tui {
         // --no-tui flag: run tests directly without TUI
         println!("\nRunning tests in {} project(s):", project_paths.len());
@@ -167,45 +305,46 @@ fn run_pr_mode(url: &str, path: Option<std::path::PathBuf>, no_tui: bool) {
             println!("  - {}", proj.display());
         }
 
-        let filter_parts: Vec<String> = changed_tests
-            .iter()
-            .map(|t| format!("FullyQualifiedName~{}", t.method_name))
-            .collect();
-        let filter = filter_parts.join("|");
+        // One `dotnet test` per batch, so long selections stay under the command line limit
+        let filters = methods_filters(resolve_changed_methods(&repo_root, &changed_tests));
 
-        println!("\nFilter: {}", filter);
+        for (i, filter) in filters.iter().enumerate() {
+            println!("\nFilter {}/{}: {}", i + 1, filters.len(), filter);
+        }
         println!("----------------------------------------");
 
-        // Run dotnet test for each project
+        // Run dotnet test for each project and batch
         for proj in &project_paths {
-            println!("\nRunning: dotnet test {}", proj.display());
-            let status = Command::new("dotnet")
-                .args([
-                    "test",
-                    proj.to_str().unwrap(),
-                    "--no-build",
-                    "--filter",
-                    &filter,
-                ])
-                .status();
-
-            match status {
-                Ok(s) => {
-                    if !s.success() {
-                        std::process::exit(s.code().unwrap_or(1));
+            for filter in &filters {
+                println!("\nRunning: dotnet test {}", proj.display());
+                let status = runner::dotnet_command()
+                    .args([
+                        "test",
+                        proj.to_str().unwrap(),
+                        "--no-build",
+                        "--filter",
+                        filter,
+                    ])
+                    .args(startup.configuration().iter().flat_map(|c| ["--configuration", *c]))
+                    .args(&startup.runner.extra_args)
+                    .status();
+
+                match status {
+                    Ok(s) => {
+                        if !s.success() {
+                            std::process::exit(s.code().unwrap_or(1));
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to run dotnet test: {}", e);
+                        std::process::exit(1);
                     }
-                }
-                Err(e) => {
-                    eprintln!("Failed to run dotnet test: {}", e);
-                    std::process::exit(1);
                 }
             }
         }
     } else {
         // Launch TUI with only the changed projects (not all projects in solution)
-        let start_dir = path.unwrap_or_else(|| env::current_dir().unwrap());
-        
-        let (projects, discovery_rx) = match discover_projects_from_paths(project_paths.clone()) {
+        let (projects, discovery_rx) = match discover_projects_from_paths(project_paths, startup.configuration(), &startup.runner.extra_args) {
             Ok(result) => result,
             Err(e) => {
                 eprintln!("Failed to discover projects: {}", e);
@@ -213,37 +352,81 @@ fn run_pr_mode(url: &str, path: Option<std::path::PathBuf>, no_tui: bool) {
             }
         };
 
-        // Use the first project's parent or start_dir as solution_dir
-        let solution_dir = project_paths.first()
-            .and_then(|p| p.parent())
-            .map(|p| p.to_path_buf())
-            .unwrap_or(start_dir);
 
         let context = Some(format!("Running Tests for PR #{}", pr_info.number));
 
-        if let Err(e) = app::run_with_preselected(projects, solution_dir, discovery_rx, changed_tests, context) {
+        if let Err(e) = app::run_with_preselected(projects, solution_dir, discovery_rx, changed_tests, context, overrides) {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
     }
 }
 
-/// Find the .csproj file for a given source file by searching parent directories
-fn find_csproj_for_file(file_path: &std::path::Path) -> Option<std::path::PathBuf> {
-    let mut dir = file_path.parent()?;
-    
-    loop {
-        // Look for .csproj files in this directory
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                let path = entry.path();
-                if path.extension().is_some_and(|ext| ext == "csproj") {
-                    return Some(path);
-                }
+/// Print how results changed between two result files.
+/// Exits with code 1 when there are new failures, so CI can gate on regressions.
+fn run_diff_mode(before: &std::path::Path, after: &std::path::Path) {
+    let load = |path: &std::path::Path| match load_snapshot(path) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            eprintln!("Failed to load results: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let (before_snapshot, after_snapshot) = (load(before), load(after));
+    let changes = diff_runs(&before_snapshot, &after_snapshot);
+
+    println!("Comparing {} -> {}", before.display(), after.display());
+    if changes.is_empty() {
+        println!("\nNo changes across {} tests.", after_snapshot.len());
+        return;
+    }
+
+    let counts = summarize(&changes);
+    let mut current = None;
+    for change in &changes {
+        if current != Some(change.kind) {
+            current = Some(change.kind);
+            let count = counts.iter().find(|(kind, _)| *kind == change.kind).map_or(0, |(_, n)| *n);
+            println!("\n{}:", change.kind.describe(count));
+        }
+        match (change.kind, change.before_ms, change.after_ms) {
+            (ChangeKind::Slower, Some(before_ms), Some(after_ms)) => {
+                println!("  {}  {} ms -> {} ms", change.name, before_ms, after_ms);
             }
+            _ => println!("  {}", change.name),
         }
-        
-        // Move to parent directory
-        dir = dir.parent()?;
+    }
+
+    let summary: Vec<String> = counts
+        .iter()
+        .map(|(kind, count)| kind.describe(*count))
+        .collect();
+    println!("\nSummary: {}", summary.join(", "));
+
+    if changes.iter().any(|c| c.kind == ChangeKind::NewFailure) {
+        std::process::exit(1);
     }
 }
+
+/// Fully-qualified names of the changed test methods, read from the checked-out
+/// test files. Falls back to the name guessed from the file path when a method
+/// isn't found locally (e.g. the PR branch isn't checked out).
+fn resolve_changed_methods(repo_root: &std::path::Path, changed_tests: &[git::ChangedTest]) -> Vec<String> {
+    let mut files = std::collections::HashMap::new();
+    changed_tests
+        .iter()
+        .map(|test| {
+            let methods = files
+                .entry(&test.file_path)
+                .or_insert_with(|| parse_test_file(&repo_root.join(&test.file_path)));
+            match methods.iter().find(|m| m.method_name == test.method_name) {
+                Some(method) => method.full_name(),
+                None => {
+                    eprintln!("Warning: {} not found in {}; guessing its namespace", test.method_name, test.file_path);
+                    test.full_name.clone()
+                }
+            }
+        })
+        .collect()
+}
+
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use super::{Test, TestStatus};
+
+/// A run is "significantly slower" when it takes at least this many times as long...
+const SLOWER_RATIO: f64 = 1.5;
+/// ...and at least this much longer, so jitter on fast tests isn't reported.
+const SLOWER_MIN_DELTA_MS: u64 = 100;
+
+/// How a test's result changed between two runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    NewFailure,
+    Fixed,
+    StillFailing,
+    NewlySkipped,
+    Slower,
+}
+
+impl ChangeKind {
+    /// Count of tests with this change, e.g. "2 new failures" or "1 fixed".
+    pub fn describe(self, count: usize) -> String {
+        let label = match self {
+            ChangeKind::NewFailure if count == 1 => "new failure",
+            ChangeKind::NewFailure => "new failures",
+            ChangeKind::Fixed => "fixed",
+            ChangeKind::StillFailing => "still failing",
+            ChangeKind::NewlySkipped => "newly skipped",
+            ChangeKind::Slower => "slower",
+        };
+        format!("{} {}", count, label)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestChange {
+    pub name: String,
+    pub kind: ChangeKind,
+    pub before_ms: Option<u64>,
+    pub after_ms: Option<u64>,
+}
+
+/// Outcome and duration of each test in a run, keyed by fully-qualified name.
+#[derive(Debug, Clone, Default)]
+pub struct RunSnapshot {
+    tests: HashMap<String, (TestStatus, Option<u64>)>,
+}
+
+impl RunSnapshot {
+    pub fn insert(&mut self, name: String, status: TestStatus, duration_ms: Option<u64>) {
+        self.tests.insert(name, (status, duration_ms));
+    }
+
+    /// Snapshot of the given tests, skipping those without a result.
+    pub fn from_tests<'a>(tests: impl IntoIterator<Item = &'a Test>) -> Self {
+        let mut snapshot = RunSnapshot::default();
+        for test in tests {
+            if matches!(test.status, TestStatus::Passed | TestStatus::Failed | TestStatus::Skipped) {
+                snapshot.insert(test.full_name.clone(), test.status.clone(), test.duration_ms);
+            }
+        }
+        snapshot
+    }
+
+    /// Overlay `other` onto this snapshot, keeping the latest result per test.
+    pub fn merge(&mut self, other: RunSnapshot) {
+        self.tests.extend(other.tests);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tests.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tests.len()
+    }
+}
+
+/// Classify every test in `after` against `before`. Tests missing from
+/// `before` count as new failures when they fail; unchanged tests are omitted.
+/// Changes are ordered by kind, then name.
+pub fn diff_runs(before: &RunSnapshot, after: &RunSnapshot) -> Vec<TestChange> {
+    let mut changes = Vec::new();
+    for (name, (status, after_ms)) in &after.tests {
+        let previous = before.tests.get(name);
+        let before_status = previous.map(|(s, _)| s);
+        let before_ms = previous.and_then(|(_, d)| *d);
+
+        let kind = match (before_status, status) {
+            (Some(TestStatus::Failed), TestStatus::Failed) => Some(ChangeKind::StillFailing),
+            (_, TestStatus::Failed) => Some(ChangeKind::NewFailure),
+            (Some(TestStatus::Failed), TestStatus::Passed) => Some(ChangeKind::Fixed),
+            (Some(TestStatus::Passed | TestStatus::Failed), TestStatus::Skipped) => Some(ChangeKind::NewlySkipped),
+            (Some(TestStatus::Passed), TestStatus::Passed) if is_slower(before_ms, *after_ms) => {
+                Some(ChangeKind::Slower)
+            }
+            _ => None,
+        };
+
+        if let Some(kind) = kind {
+            changes.push(TestChange {
+                name: name.clone(),
+                kind,
+                before_ms,
+                after_ms: *after_ms,
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.name.cmp(&b.name)));
+    changes
+}
+
+fn is_slower(before_ms: Option<u64>, after_ms: Option<u64>) -> bool {
+    match (before_ms, after_ms) {
+        (Some(before), Some(after)) => {
+            after >= before + SLOWER_MIN_DELTA_MS && after as f64 >= before as f64 * SLOWER_RATIO
+        }
+        _ => false,
+    }
+}
+
+/// Count changes per kind, as `(kind, count)` in kind order, skipping zero counts.
+pub fn summarize(changes: &[TestChange]) -> Vec<(ChangeKind, usize)> {
+    let mut counts: Vec<(ChangeKind, usize)> = Vec::new();
+    for change in changes {
+        match counts.last_mut() {
+            Some((kind, count)) if *kind == change.kind => *count += 1,
+            _ => counts.push((change.kind, 1)),
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, TestStatus, u64)]) -> RunSnapshot {
+        let mut snapshot = RunSnapshot::default();
+        for (name, status, ms) in entries {
+            snapshot.insert(name.to_string(), status.clone(), Some(*ms));
+        }
+        snapshot
+    }
+
+    fn kinds(changes: &[TestChange]) -> Vec<(&str, ChangeKind)> {
+        changes.iter().map(|c| (c.name.as_str(), c.kind)).collect()
+    }
+
+    #[test]
+    fn test_diff_runs_classifies_changes() {
+        let before = snapshot(&[
+            ("A.Broken", TestStatus::Passed, 10),
+            ("A.Repaired", TestStatus::Failed, 10),
+            ("A.Flaky", TestStatus::Failed, 10),
+            ("A.Ignored", TestStatus::Passed, 10),
+            ("A.Quarantined", TestStatus::Failed, 10),
+            ("A.Steady", TestStatus::Passed, 10),
+        ]);
+        let after = snapshot(&[
+            ("A.Broken", TestStatus::Failed, 10),
+            ("A.Repaired", TestStatus::Passed, 10),
+            ("A.Flaky", TestStatus::Failed, 10),
+            ("A.Ignored", TestStatus::Skipped, 0),
+            ("A.Quarantined", TestStatus::Skipped, 0),
+            ("A.Steady", TestStatus::Passed, 12),
+        ]);
+
+        assert_eq!(
+            kinds(&diff_runs(&before, &after)),
+            [
+                ("A.Broken", ChangeKind::NewFailure),
+                ("A.Repaired", ChangeKind::Fixed),
+                ("A.Flaky", ChangeKind::StillFailing),
+                ("A.Ignored", ChangeKind::NewlySkipped),
+                ("A.Quarantined", ChangeKind::NewlySkipped),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_runs_new_test_failing_is_new_failure() {
+        let before = snapshot(&[("A.Old", TestStatus::Passed, 10)]);
+        let after = snapshot(&[("A.New", TestStatus::Failed, 10), ("A.Newer", TestStatus::Passed, 10)]);
+        assert_eq!(kinds(&diff_runs(&before, &after)), [("A.New", ChangeKind::NewFailure)]);
+    }
+
+    #[test]
+    fn test_diff_runs_slower_needs_ratio_and_delta() {
+        let before = snapshot(&[
+            ("A.Fast", TestStatus::Passed, 10),
+            ("A.Slow", TestStatus::Passed, 1000),
+            ("A.Slightly", TestStatus::Passed, 1000),
+        ]);
+        let after = snapshot(&[
+            ("A.Fast", TestStatus::Passed, 60),       // 6x but only 50 ms
+            ("A.Slow", TestStatus::Passed, 2500),     // 2.5x and 1.5 s
+            ("A.Slightly", TestStatus::Passed, 1200), // 200 ms but only 1.2x
+        ]);
+        let changes = diff_runs(&before, &after);
+        assert_eq!(kinds(&changes), [("A.Slow", ChangeKind::Slower)]);
+        assert_eq!(changes[0].before_ms, Some(1000));
+        assert_eq!(changes[0].after_ms, Some(2500));
+    }
+
+    #[test]
+    fn test_snapshot_merge_keeps_latest() {
+        let mut snapshot = snapshot(&[("A.One", TestStatus::Failed, 10), ("A.Two", TestStatus::Passed, 10)]);
+        snapshot.merge(self::snapshot(&[("A.One", TestStatus::Passed, 10)]));
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot.tests["A.One"].0, TestStatus::Passed);
+    }
+
+    #[test]
+    fn test_snapshot_from_tests_skips_tests_without_result() {
+        let mut passed = Test::new("Passed".to_string(), "A.Passed".to_string());
+        passed.status = TestStatus::Passed;
+        let mut running = Test::new("Running".to_string(), "A.Running".to_string());
+        running.status = TestStatus::Running;
+        let not_run = Test::new("NotRun".to_string(), "A.NotRun".to_string());
+
+        let snapshot = RunSnapshot::from_tests([&passed, &running, &not_run]);
+        assert_eq!(snapshot.len(), 1);
+        assert!(snapshot.tests.contains_key("A.Passed"));
+    }
+
+    #[test]
+    fn test_change_kind_describe() {
+        assert_eq!(ChangeKind::NewFailure.describe(1), "1 new failure");
+        assert_eq!(ChangeKind::NewFailure.describe(3), "3 new failures");
+        assert_eq!(ChangeKind::Fixed.describe(2), "2 fixed");
+        assert_eq!(ChangeKind::Slower.describe(1), "1 slower");
+    }
+
+    #[test]
+    fn test_summarize() {
+        let before = snapshot(&[("A", TestStatus::Passed, 1), ("B", TestStatus::Passed, 1), ("C", TestStatus::Failed, 1)]);
+        let after = snapshot(&[("A", TestStatus::Failed, 1), ("B", TestStatus::Failed, 1), ("C", TestStatus::Passed, 1)]);
+        assert_eq!(
+            summarize(&diff_runs(&before, &after)),
+            [(ChangeKind::NewFailure, 2), (ChangeKind::Fixed, 1)]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::{TestOutcome, TestReport, TestResult};
+
+use super::{Test, TestProject, TestStatus};
+
+/// Apply a run's results to the project's tests. Results are matched by the
+/// identity from the result file's test definitions first; name heuristics
+/// cover results without definitions.
+pub fn apply_report(project: &mut TestProject, report: &TestReport) {
+    let results = &report.results;
+    // Build index: map from test_name -> Vec<(index, &result)>
+    // Also map from suffix (last segment after '.') -> Vec<(index, &result)>
+    let mut by_full_name: HashMap<&str, Vec<(usize, &TestResult)>> = HashMap::new();
+    let mut by_suffix: HashMap<&str, Vec<(usize, &TestResult)>> = HashMap::new();
+
+    for (i, r) in results.iter().enumerate() {
+        by_full_name.entry(r.test_name.as_str()).or_default().push((i, r));
+        // Extract the part after the last '.' for suffix matching
+        if let Some(pos) = r.test_name.rfind('.') {
+            let suffix = &r.test_name[pos + 1..];
+            by_suffix.entry(suffix).or_default().push((i, r));
+        }
+    }
+
+    let mut consumed = vec![false; results.len()];
+
+    // Pass 0: exact identity from the result file's test definitions
+    let matched = match_by_identity(project, report, &mut consumed);
+
+    // Remaining passes are name heuristics for results without definitions. A
+    // result whose identity is known but matched no test stays unmatched.
+    for (i, r) in results.iter().enumerate() {
+        if report.qualified_name(r).is_some() {
+            consumed[i] = true;
+        }
+    }
+
+    // Pass 1: precise full_name matching, then suffix matching
+    for (c, class) in project.classes.iter_mut().enumerate() {
+        for (t, test) in class.tests.iter_mut().enumerate() {
+            if matched.contains(&(c, t)) {
+                continue;
+            }
+            // Try exact match first
+            let found = by_full_name.get(test.full_name.as_str())
+                .and_then(|entries| entries.iter().find(|(i, _)| !consumed[*i]))
+                .or_else(|| {
+                    // Try suffix match: result ends with ".{test.full_name}"
+                    by_suffix.get(test.full_name.as_str())
+                        .and_then(|entries| entries.iter().find(|(i, _)| !consumed[*i]))
+                });
+
+            if let Some(&(i, result)) = found {
+                consumed[i] = true;
+                apply_result(test, result);
+            }
+        }
+    }
+
+    // Pass 1.5: endsWith matching for multi-segment names (e.g. "Class.Method" vs "Namespace.Class.Method")
+    let suffix_needle = ".";
+    for (c, class) in project.classes.iter_mut().enumerate() {
+        for (t, test) in class.tests.iter_mut().enumerate() {
+            if matched.contains(&(c, t)) {
+                continue;
+            }
+            let needle = format!("{}{}", suffix_needle, test.full_name);
+            let found = results.iter().enumerate()
+                .find(|(i, r)| !consumed[*i] && (r.test_name.ends_with(&needle) || r.test_name == test.full_name));
+            if let Some((i, result)) = found {
+                consumed[i] = true;
+                apply_result(test, result);
+            }
+        }
+    }
+
+    // Pass 2: bare name fallback for remaining unmatched tests/results
+    for class in &mut project.classes {
+        for test in &mut class.tests {
+            if test.status == TestStatus::Running {
+                // Try test.name directly
+                let found = by_full_name.get(test.name.as_str())
+                    .and_then(|entries| entries.iter().find(|(i, _)| !consumed[*i]))
+                    .or_else(|| {
+                        // Try bare method name (after last '.') for Class.Method style names
+                        let method = test.name.rsplit('.').next().unwrap_or(&test.name);
+                        by_full_name.get(method)
+                            .and_then(|entries| entries.iter().find(|(i, _)| !consumed[*i]))
+                    });
+
+                if let Some(&(i, result)) = found {
+                    consumed[i] = true;
+                    apply_result(test, result);
+                }
+            }
+        }
+    }
+}
+
+/// Match results to tests by the fully-qualified name resolved from the result
+/// file's test definitions (TRX `testId` -> `TestMethod` className/name). This is
+/// exact, so duplicate method names, custom display names and nested classes
+/// can't pick up another test's result. Returns the (class, test) indices matched.
+fn match_by_identity(project: &mut TestProject, report: &TestReport, consumed: &mut [bool]) -> HashSet<(usize, usize)> {
+    // Qualified name -> result indices. Parameterized cases are also indexed
+    // under the bare method so a single discovered entry picks them all up.
+    let mut by_qualified: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut by_method: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, result) in report.results.iter().enumerate() {
+        let Some(name) = report.qualified_name(result) else {
+            continue;
+        };
+        if let Some((method, _)) = name.split_once('(') {
+            by_method.entry(method.to_string()).or_default().push(i);
+        }
+        by_qualified.entry(name).or_default().push(i);
+    }
+
+    let mut matched = HashSet::new();
+    if by_qualified.is_empty() {
+        return matched;
+    }
+    for (c, class) in project.classes.iter_mut().enumerate() {
+        for (t, test) in class.tests.iter_mut().enumerate() {
+            let candidates: Vec<usize> = by_qualified
+                .get(&test.full_name)
+                .or_else(|| by_method.get(&test.full_name))
+                .map(|indices| indices.iter().copied().filter(|&i| !consumed[i]).collect())
+                .unwrap_or_default();
+            let [first, ..] = candidates[..] else {
+                continue;
+            };
+            // One entry standing for several cases shows the first failure, if any
+            let shown = candidates
+                .iter()
+                .copied()
+                .find(|&i| report.results[i].outcome == TestOutcome::Failed)
+                .unwrap_or(first);
+            for &i in &candidates {
+                consumed[i] = true;
+            }
+            apply_result(test, &report.results[shown]);
+            matched.insert((c, t));
+        }
+    }
+    matched
+}
+
+/// Show a single result on a test.
+pub fn apply_result(test: &mut Test, result: &TestResult) {
+    test.status = match result.outcome {
+        TestOutcome::Passed => TestStatus::Passed,
+        TestOutcome::Failed => TestStatus::Failed,
+        TestOutcome::Skipped => TestStatus::Skipped,
+    };
+    test.duration_ms = Some(result.duration_ms);
+    test.error_message = result.error_message.clone();
+    test.output = result.output.clone();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::TestClass;
+    use crate::parser::{parse_report, ParseOptions, ResultFormat};
+    use std::path::PathBuf;
+
+    /// A project whose tests are all Running, as at the end of a run.
+    fn project(classes: &[(&str, &str, &[&str])]) -> TestProject {
+        let mut project = TestProject::new("Tests".to_string(), PathBuf::from("Tests.csproj"));
+        for (namespace, name, methods) in classes {
+            let mut class = TestClass::new(name.to_string(), namespace.to_string());
+            for method in *methods {
+                let mut test = Test::new(method.to_string(), format!("{}.{}", class.full_name, method));
+                test.status = TestStatus::Running;
+                class.tests.push(test);
+            }
+            project.classes.push(class);
+        }
+        project
+    }
+
+    /// A TRX report from (test name, outcome, definition class, definition method).
+    /// Results without a class have no definition.
+    fn report(results: &[(&str, &str, &str, &str)]) -> TestReport {
+        let mut xml = String::from("<TestRun><Results>");
+        let mut definitions = String::new();
+        for (i, (name, outcome, class_name, method)) in results.iter().enumerate() {
+            if class_name.is_empty() {
+                xml += &format!(r#"<UnitTestResult testName="{}" outcome="{}" />"#, name, outcome);
+            } else {
+                xml += &format!(r#"<UnitTestResult testId="id-{}" testName="{}" outcome="{}" />"#, i, name, outcome);
+                definitions += &format!(
+                    r#"<UnitTest name="{}" id="id-{}"><TestMethod className="{}" name="{}" /></UnitTest>"#,
+                    name, i, class_name, method
+                );
+            }
+        }
+        xml += &format!("</Results><TestDefinitions>{}</TestDefinitions></TestRun>", definitions);
+        parse_report(xml.as_bytes(), ResultFormat::Trx, &ParseOptions::default()).unwrap()
+    }
+
+    fn status(project: &TestProject, full_name: &str) -> TestStatus {
+        project.classes.iter()
+            .flat_map(|c| &c.tests)
+            .find(|t| t.full_name == full_name)
+            .map(|t| t.status.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_method_names() {
+        let mut project = project(&[("App", "FooTests", &["Save"]), ("App", "BarTests", &["Save"])]);
+        // NUnit reports bare method names; the failing one belongs to BarTests
+        let report = report(&[
+            ("Save", "Failed", "App.BarTests", "Save"),
+            ("Save", "Passed", "App.FooTests", "Save"),
+        ]);
+        apply_report(&mut project, &report);
+        assert_eq!(status(&project, "App.FooTests.Save"), TestStatus::Passed);
+        assert_eq!(status(&project, "App.BarTests.Save"), TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_custom_display_name() {
+        let mut project = project(&[("App", "CalcTests", &["Add", "Sub"])]);
+        let report = report(&[
+            ("Subtracts two numbers", "Failed", "App.CalcTests", "Sub"),
+            ("Adds two numbers", "Passed", "App.CalcTests", "Add"),
+        ]);
+        apply_report(&mut project, &report);
+        assert_eq!(status(&project, "App.CalcTests.Add"), TestStatus::Passed);
+        assert_eq!(status(&project, "App.CalcTests.Sub"), TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_nested_class() {
+        let mut project = project(&[("App", "Outer", &["Run"]), ("App", "Outer+Inner", &["Run"])]);
+        let report = report(&[
+            ("App.Outer+Inner.Run", "Failed", "App.Outer+Inner", "Run"),
+            ("App.Outer.Run", "Passed", "App.Outer", "Run"),
+        ]);
+        apply_report(&mut project, &report);
+        assert_eq!(status(&project, "App.Outer.Run"), TestStatus::Passed);
+        assert_eq!(status(&project, "App.Outer+Inner.Run"), TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_result_with_unmatched_definition_is_not_guessed() {
+        let mut project = project(&[("App", "CalcTests", &["Add"])]);
+        // Declared in a class that wasn't discovered, so it must not land on CalcTests.Add
+        let report = report(&[("Add", "Failed", "App.LegacyTests", "Add")]);
+        apply_report(&mut project, &report);
+        assert_eq!(status(&project, "App.CalcTests.Add"), TestStatus::Running);
+    }
+
+    #[test]
+    fn test_heuristic_fallback_without_definitions() {
+        let mut project = project(&[("App", "CalcTests", &["Add", "Sub", "Mul"])]);
+        let report = report(&[
+            ("App.CalcTests.Add", "Passed", "", ""),
+            ("Sub", "Failed", "", ""),
+            ("Mul", "NotExecuted", "", ""),
+        ]);
+        apply_report(&mut project, &report);
+        assert_eq!(status(&project, "App.CalcTests.Add"), TestStatus::Passed);
+        assert_eq!(status(&project, "App.CalcTests.Sub"), TestStatus::Failed);
+        assert_eq!(status(&project, "App.CalcTests.Mul"), TestStatus::Skipped);
+    }
+}
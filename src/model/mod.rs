@@ -1,5 +1,13 @@
+mod diff;
+mod matching;
+mod output;
 mod project;
+mod repeat;
 mod test;
 
+pub use diff::{diff_runs, summarize, ChangeKind, RunSnapshot};
+pub use matching::{apply_report, apply_result};
+pub use output::{OutputLine, OutputSource};
 pub use project::TestProject;
+pub use repeat::RepeatStats;
 pub use test::{Test, TestClass, TestStatus};
@@ -0,0 +1,21 @@
+/// Where a line in the output pane came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputSource {
+    Stdout,
+    Stderr,
+    /// Messages from testament itself, e.g. the command being run
+    Testament,
+}
+
+/// A line of output from `dotnet`, tagged with its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLine {
+    pub source: OutputSource,
+    pub content: String,
+}
+
+impl OutputLine {
+    pub fn new(source: OutputSource, content: impl Into<String>) -> Self {
+        Self { source, content: content.into() }
+    }
+}
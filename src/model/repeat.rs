@@ -0,0 +1,96 @@
+use super::TestStatus;
+
+/// Outcomes of one test over a repeated run, to tell flaky tests from broken ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepeatStats {
+    pub passed: u32,
+    pub failed: u32,
+    pub skipped: u32,
+    durations_ms: Vec<u64>,
+}
+
+impl RepeatStats {
+    /// Count one run's result. Tests without a result (not run, still running) are ignored.
+    pub fn record(&mut self, status: &TestStatus, duration_ms: Option<u64>) {
+        match status {
+            TestStatus::Passed => self.passed += 1,
+            TestStatus::Failed => self.failed += 1,
+            TestStatus::Skipped => self.skipped += 1,
+            TestStatus::NotRun | TestStatus::Running => return,
+        }
+        if let Some(ms) = duration_ms {
+            self.durations_ms.push(ms);
+        }
+    }
+
+    pub fn runs(&self) -> u32 {
+        self.passed + self.failed + self.skipped
+    }
+
+    /// Share of the runs that produced a verdict (passed or failed) which failed.
+    pub fn failure_rate(&self) -> f64 {
+        match self.passed + self.failed {
+            0 => 0.0,
+            decided => self.failed as f64 / decided as f64,
+        }
+    }
+
+    /// Both passed and failed: the verdict depends on something other than the code.
+    pub fn is_flaky(&self) -> bool {
+        self.passed > 0 && self.failed > 0
+    }
+
+    /// (min, median, max) duration in milliseconds.
+    pub fn duration_spread(&self) -> Option<(u64, u64, u64)> {
+        let mut sorted = self.durations_ms.clone();
+        sorted.sort_unstable();
+        Some((*sorted.first()?, sorted[sorted.len() / 2], *sorted.last()?))
+    }
+
+    /// "18/20 passed, 2 failed (10% failure rate)"
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{}/{} passed", self.passed, self.runs());
+        if self.failed > 0 {
+            summary.push_str(&format!(", {} failed", self.failed));
+        }
+        if self.skipped > 0 {
+            summary.push_str(&format!(", {} skipped", self.skipped));
+        }
+        if self.is_flaky() {
+            summary.push_str(&format!(" ({:.0}% failure rate)", self.failure_rate() * 100.0));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_rate() {
+        let mut stats = RepeatStats::default();
+        for _ in 0..3 {
+            stats.record(&TestStatus::Passed, Some(10));
+        }
+        stats.record(&TestStatus::Failed, Some(250));
+        stats.record(&TestStatus::NotRun, None);
+
+        assert_eq!(stats.runs(), 4);
+        assert!(stats.is_flaky());
+        assert_eq!(stats.failure_rate(), 0.25);
+        assert_eq!(stats.duration_spread(), Some((10, 10, 250)));
+        assert_eq!(stats.summary(), "3/4 passed, 1 failed (25% failure rate)");
+    }
+
+    #[test]
+    fn test_consistent_results_are_not_flaky() {
+        let mut stats = RepeatStats::default();
+        stats.record(&TestStatus::Failed, None);
+        stats.record(&TestStatus::Failed, None);
+        assert!(!stats.is_flaky());
+        assert_eq!(stats.failure_rate(), 1.0);
+        assert_eq!(stats.duration_spread(), None);
+        assert_eq!(stats.summary(), "0/2 passed, 2 failed");
+    }
+}
@@ -1,3 +1,5 @@
+use super::{ChangeKind, RepeatStats};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TestStatus {
     NotRun,
@@ -15,6 +17,9 @@ pub struct Test {
     pub status: TestStatus,
     pub duration_ms: Option<u64>,
     pub error_message: Option<String>,
+    pub output: Option<String>,
+    pub change: Option<ChangeKind>, // Change since the previous run, if any
+    pub repeat: Option<RepeatStats>, // Outcomes over the latest repeated run
 }
 
 impl Test {
@@ -27,6 +32,9 @@ impl Test {
             status: TestStatus::NotRun,
             duration_ms: None,
             error_message: None,
+            output: None,
+            change: None,
+            repeat: None,
         }
     }
 }
@@ -105,6 +113,8 @@ mod tests {
         assert_eq!(test.status, TestStatus::NotRun);
         assert!(test.duration_ms.is_none());
         assert!(test.error_message.is_none());
+        assert!(test.output.is_none());
+        assert!(test.change.is_none());
     }
 
     #[test]
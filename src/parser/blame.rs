@@ -0,0 +1,166 @@
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::error::{Result, TestamentError};
+
+use super::report::{attr_value, TestDefinition, TestOutcome, TestReport, TestResult};
+
+/// How testhost went down, as told by the blame data collector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abort {
+    Crash,
+    /// Killed by `--blame-hang` after a test ran past the hang timeout
+    Hang,
+}
+
+/// A test that was running when testhost crashed or hung.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlamedTest {
+    /// Fully-qualified name from the blame sequence file
+    pub name: String,
+    pub abort: Abort,
+    /// Crash or hang dump, if one was collected
+    pub dump: Option<PathBuf>,
+}
+
+impl BlamedTest {
+    pub fn message(&self) -> String {
+        let mut message = match self.abort {
+            Abort::Crash => "Test host crashed while this test was running.".to_string(),
+            Abort::Hang => "Test hung: the blame hang timeout expired while this test was running.".to_string(),
+        };
+        if let Some(dump) = &self.dump {
+            message.push_str(&format!("\nDump: {}", dump.display()));
+        }
+        message
+    }
+}
+
+/// Parse a blame `Sequence_*.xml` attachment and return the tests that never
+/// completed, in the order they started.
+///
+/// Layout: `<TestSequence><Test Name="Ns.Class.Method" Source="..." Completed="False"/></TestSequence>`.
+/// Older test platforms don't write `Completed`; there the last test started is the culprit.
+pub fn parse_blame_sequence<R: BufRead>(source: R) -> Result<Vec<String>> {
+    let mut reader = Reader::from_reader(source);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut started = Vec::new();
+    let mut incomplete = Vec::new();
+    let mut has_completion = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.name().as_ref() == b"Test" => {
+                let mut name = String::new();
+                let mut completed = None;
+                for attr in e.attributes().flatten() {
+                    match attr.key.as_ref() {
+                        b"Name" => name = attr_value(&attr),
+                        b"Completed" => completed = Some(attr_value(&attr).eq_ignore_ascii_case("true")),
+                        _ => {}
+                    }
+                }
+                if name.is_empty() {
+                    continue;
+                }
+                has_completion |= completed.is_some();
+                if completed == Some(false) {
+                    incomplete.push(name.clone());
+                }
+                started.push(name);
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(TestamentError::ResultParse(format!(
+                    "Blame sequence parse error: {}",
+                    e
+                )))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if has_completion {
+        Ok(incomplete)
+    } else {
+        Ok(started.pop().into_iter().collect())
+    }
+}
+
+/// A failed result for each blamed test, so the crash or hang (and its dump)
+/// shows up on the test itself.
+pub fn blame_report(blamed: &[BlamedTest]) -> TestReport {
+    let mut report = TestReport::default();
+    for (index, test) in blamed.iter().enumerate() {
+        let method = test.name.split('(').next().unwrap_or(&test.name);
+        let (class_name, method_name) = method.rsplit_once('.').unwrap_or(("", method));
+        let id = format!("blame-{}", index);
+        report.push(
+            TestResult {
+                test_name: test.name.clone(),
+                outcome: TestOutcome::Failed,
+                duration_ms: 0,
+                error_message: Some(test.message()),
+                output: None,
+                test_id: Some(id.clone()),
+            },
+            TestDefinition {
+                id,
+                class_name: class_name.to_string(),
+                method_name: method_name.to_string(),
+                storage: String::new(),
+            },
+        );
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blame_sequence_incomplete_tests() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<TestSequence>
+  <Test Name="Ns.Tests.Fast" Source="/src/Tests/bin/Tests.dll" Completed="True" />
+  <Test Name="Ns.Tests.Hangs" Source="/src/Tests/bin/Tests.dll" Completed="False" />
+  <Test Name="Ns.Tests.Parse(&quot;x&quot;)" Source="/src/Tests/bin/Tests.dll" Completed="False" />
+</TestSequence>"#;
+        assert_eq!(
+            parse_blame_sequence(xml.as_bytes()).unwrap(),
+            ["Ns.Tests.Hangs", "Ns.Tests.Parse(\"x\")"]
+        );
+    }
+
+    #[test]
+    fn test_parse_blame_sequence_without_completion_blames_last() {
+        let xml = r#"<TestSequence>
+  <Test Name="Ns.Tests.A" Source="Tests.dll" />
+  <Test Name="Ns.Tests.B" Source="Tests.dll" />
+</TestSequence>"#;
+        assert_eq!(parse_blame_sequence(xml.as_bytes()).unwrap(), ["Ns.Tests.B"]);
+    }
+
+    #[test]
+    fn test_blame_report() {
+        let report = blame_report(&[BlamedTest {
+            name: "Ns.Tests.Parse(1)".to_string(),
+            abort: Abort::Crash,
+            dump: Some(PathBuf::from("/tmp/testhost_crashdump.dmp")),
+        }]);
+        let result = &report.results[0];
+        assert_eq!(result.outcome, TestOutcome::Failed);
+        assert_eq!(report.qualified_name(result).as_deref(), Some("Ns.Tests.Parse(1)"));
+        assert_eq!(
+            result.error_message.as_deref(),
+            Some("Test host crashed while this test was running.\nDump: /tmp/testhost_crashdump.dmp")
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+use std::path::Path;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::error::{Result, TestamentError};
+
+use super::csharp::tested_class_names;
+use super::report::attr_value;
+
+/// Covered and coverable lines and branches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoverageCounts {
+    pub lines_covered: u32,
+    pub lines_valid: u32,
+    pub branches_covered: u32,
+    pub branches_valid: u32,
+}
+
+impl CoverageCounts {
+    pub fn add(&mut self, other: CoverageCounts) {
+        self.lines_covered += other.lines_covered;
+        self.lines_valid += other.lines_valid;
+        self.branches_covered += other.branches_covered;
+        self.branches_valid += other.branches_valid;
+    }
+
+    /// Percentage of lines covered; None when there are no coverable lines.
+    pub fn line_percent(&self) -> Option<f64> {
+        percent(self.lines_covered, self.lines_valid)
+    }
+
+    /// Percentage of branches covered; None when there are no branches.
+    pub fn branch_percent(&self) -> Option<f64> {
+        percent(self.branches_covered, self.branches_valid)
+    }
+
+    /// "78.3% lines (470/600), 61.0% branches (61/100)"
+    pub fn summary(&self) -> String {
+        let mut summary = match self.line_percent() {
+            Some(p) => format!("{:.1}% lines ({}/{})", p, self.lines_covered, self.lines_valid),
+            None => "no coverable lines".to_string(),
+        };
+        if let Some(p) = self.branch_percent() {
+            summary.push_str(&format!(", {:.1}% branches ({}/{})", p, self.branches_covered, self.branches_valid));
+        }
+        summary
+    }
+}
+
+fn percent(covered: u32, valid: u32) -> Option<f64> {
+    (valid > 0).then(|| covered as f64 * 100.0 / valid as f64)
+}
+
+/// Hits and branch coverage of one source line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineCoverage {
+    pub hits: u64,
+    pub branches_covered: u32,
+    pub branches_valid: u32,
+}
+
+impl LineCoverage {
+    /// Combine coverage of the same line from another report.
+    fn merge(&mut self, other: LineCoverage) {
+        self.hits += other.hits;
+        self.branches_valid = self.branches_valid.max(other.branches_valid);
+        self.branches_covered = self.branches_covered.max(other.branches_covered).min(self.branches_valid);
+    }
+}
+
+/// Coverage of a class, with compiler-generated nested classes (lambdas, async
+/// state machines) folded into their declaring class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassCoverage {
+    /// Assembly (Cobertura package) the class is compiled into
+    pub assembly: String,
+    /// e.g. `Api.Services.UserService`
+    pub name: String,
+    pub file: String,
+    pub lines: BTreeMap<u32, LineCoverage>,
+}
+
+impl ClassCoverage {
+    pub fn namespace(&self) -> &str {
+        self.name.rsplit_once('.').map_or("", |(namespace, _)| namespace)
+    }
+
+    /// Class name without its namespace
+    pub fn simple_name(&self) -> &str {
+        self.name.rsplit('.').next().unwrap_or(&self.name)
+    }
+}
+
+fn count_lines<'a>(lines: impl IntoIterator<Item = &'a LineCoverage>) -> CoverageCounts {
+    let mut counts = CoverageCounts::default();
+    for line in lines {
+        counts.lines_valid += 1;
+        counts.lines_covered += u32::from(line.hits > 0);
+        counts.branches_covered += line.branches_covered;
+        counts.branches_valid += line.branches_valid;
+    }
+    counts
+}
+
+/// Line and branch coverage read from Cobertura XML (coverlet's
+/// `dotnet test --collect "XPlat Code Coverage"`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub classes: Vec<ClassCoverage>,
+}
+
+impl CoverageReport {
+    /// Add the coverage of another run, e.g. another test project covering the
+    /// same assembly. Hits on the same line add up.
+    pub fn merge(&mut self, other: CoverageReport) {
+        for class in other.classes {
+            self.add_class(class);
+        }
+    }
+
+    fn add_class(&mut self, class: ClassCoverage) {
+        let existing = self
+            .classes
+            .iter_mut()
+            .find(|c| c.assembly == class.assembly && c.name == class.name && c.file == class.file);
+        match existing {
+            Some(existing) => {
+                for (number, line) in class.lines {
+                    existing.lines.entry(number).or_default().merge(line);
+                }
+            }
+            None => self.classes.push(class),
+        }
+    }
+
+    pub fn totals(&self) -> CoverageCounts {
+        self.group_by(|c| c.assembly.clone()).into_values().fold(CoverageCounts::default(), |mut total, c| {
+            total.add(c);
+            total
+        })
+    }
+
+    /// Coverage per assembly, by name.
+    pub fn by_assembly(&self) -> BTreeMap<String, CoverageCounts> {
+        self.group_by(|c| c.assembly.clone())
+    }
+
+    /// Coverage per namespace, by name.
+    pub fn by_namespace(&self) -> BTreeMap<String, CoverageCounts> {
+        self.group_by(|c| c.namespace().to_string())
+    }
+
+    /// Coverage per source file (assembly, file), by path.
+    pub fn by_file(&self) -> BTreeMap<(String, String), CoverageCounts> {
+        self.group_by(|c| (c.assembly.clone(), c.file.clone()))
+    }
+
+    /// Coverage per class name without namespace, for matching test classes to
+    /// the classes they test.
+    pub fn by_simple_name(&self) -> HashMap<String, CoverageCounts> {
+        self.group_by(|c| c.simple_name().to_string()).into_iter().collect()
+    }
+
+    /// Group classes by `key`. Lines shared by classes of the same file (partial
+    /// classes, nested types) count once.
+    fn group_by<K: Ord>(&self, key: impl Fn(&ClassCoverage) -> K) -> BTreeMap<K, CoverageCounts> {
+        let mut lines: BTreeMap<K, BTreeMap<(&str, &str, u32), LineCoverage>> = BTreeMap::new();
+        for class in &self.classes {
+            let group = lines.entry(key(class)).or_default();
+            for (&number, &line) in &class.lines {
+                group.entry((&class.assembly, &class.file, number)).or_default().merge(line);
+            }
+        }
+        lines.into_iter().map(|(k, group)| (k, count_lines(group.values()))).collect()
+    }
+}
+
+/// Coverage of the class a test class tests, going by name (see `tested_class_names`).
+pub fn tested_class_coverage(by_simple_name: &HashMap<String, CoverageCounts>, test_class: &str) -> Option<CoverageCounts> {
+    tested_class_names(test_class).find_map(|name| by_simple_name.get(name).copied())
+}
+
+/// Parse a Cobertura XML coverage file.
+///
+/// Layout: `<coverage><sources><source>/src/</source></sources><packages><package name="Api">
+/// <classes><class name="Api.UserService" filename="Services/UserService.cs"><lines>
+/// <line number="12" hits="3" branch="True" condition-coverage="50% (1/2)"/>`. Lines
+/// are read from each class's own `<lines>`; the per-method copies are skipped.
+pub fn parse_cobertura<R: BufRead>(source: R) -> Result<CoverageReport> {
+    let mut reader = Reader::from_reader(source);
+    reader.config_mut().trim_text(true);
+
+    let mut report = CoverageReport::default();
+    let mut buf = Vec::new();
+    let mut sources: Vec<String> = Vec::new();
+    let mut in_source = false;
+    let mut assembly = String::new();
+    let mut current: Option<ClassCoverage> = None;
+    let mut in_method = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.name().as_ref() {
+                b"source" => in_source = true,
+                b"package" => assembly = attribute(&e, b"name").unwrap_or_default(),
+                b"class" => current = Some(parse_class(&e, &assembly, &sources)),
+                b"method" => in_method = true,
+                _ => {}
+            },
+            Ok(Event::Empty(e)) => match e.name().as_ref() {
+                b"line" if !in_method => {
+                    if let Some(class) = current.as_mut() {
+                        if let Some((number, line)) = parse_line(&e) {
+                            class.lines.entry(number).or_default().merge(line);
+                        }
+                    }
+                }
+                b"class" => report.add_class(parse_class(&e, &assembly, &sources)),
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_source => {
+                sources.push(e.unescape().unwrap_or_default().trim().to_string());
+            }
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"source" => in_source = false,
+                b"method" => in_method = false,
+                b"class" => {
+                    if let Some(class) = current.take() {
+                        report.add_class(class);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(TestamentError::ResultParse(format!(
+                    "Cobertura XML parse error: {}",
+                    e
+                )))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(report)
+}
+
+fn attribute(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes().flatten().find(|a| a.key.as_ref() == name).map(|a| attr_value(&a))
+}
+
+fn parse_class(e: &BytesStart, assembly: &str, sources: &[String]) -> ClassCoverage {
+    let name = attribute(e, b"name").unwrap_or_default();
+    // Nested and compiler-generated types: `Outer/Inner`, `Outer/<GetAsync>d__3`
+    let name = name.split('/').next().unwrap_or_default().to_string();
+    let file = attribute(e, b"filename").unwrap_or_default();
+    // Relative file names are relative to the (single) source directory
+    let file = match sources {
+        [source] if !file.is_empty() && Path::new(&file).is_relative() => {
+            Path::new(source).join(&file).to_string_lossy().into_owned()
+        }
+        _ => file,
+    };
+    ClassCoverage { assembly: assembly.to_string(), name, file, lines: BTreeMap::new() }
+}
+
+fn parse_line(e: &BytesStart) -> Option<(u32, LineCoverage)> {
+    let mut number = None;
+    let mut line = LineCoverage::default();
+    for attr in e.attributes().flatten() {
+        match attr.key.as_ref() {
+            b"number" => number = attr_value(&attr).parse().ok(),
+            b"hits" => line.hits = attr_value(&attr).parse().unwrap_or(0),
+            // "50% (1/2)"
+            b"condition-coverage" => {
+                let value = attr_value(&attr);
+                let counts = value.split_once('(').and_then(|(_, rest)| rest.trim_end_matches(')').split_once('/'));
+                if let Some((covered, valid)) = counts {
+                    line.branches_covered = covered.trim().parse().unwrap_or(0);
+                    line.branches_valid = valid.trim().parse().unwrap_or(0);
+                }
+            }
+            _ => {}
+        }
+    }
+    Some((number?, line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COBERTURA: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<coverage line-rate="0.6" branch-rate="0.5" version="1.9" timestamp="1700000000">
+  <sources>
+    <source>/src/Api/</source>
+  </sources>
+  <packages>
+    <package name="Api" line-rate="0.6" branch-rate="0.5" complexity="4">
+      <classes>
+        <class name="Api.Services.UserService" filename="Services/UserService.cs" line-rate="0.75" branch-rate="0.5" complexity="2">
+          <methods>
+            <method name="Get" signature="(System.Int32)" line-rate="1" branch-rate="1" complexity="1">
+              <lines>
+                <line number="10" hits="4" branch="False" />
+              </lines>
+            </method>
+          </methods>
+          <lines>
+            <line number="10" hits="4" branch="False" />
+            <line number="11" hits="4" branch="True" condition-coverage="50% (1/2)" />
+            <line number="12" hits="0" branch="False" />
+            <line number="13" hits="2" branch="False" />
+          </lines>
+        </class>
+        <class name="Api.Services.UserService/&lt;GetAsync&gt;d__3" filename="Services/UserService.cs" line-rate="1" branch-rate="1" complexity="1">
+          <methods />
+          <lines>
+            <line number="20" hits="1" branch="False" />
+          </lines>
+        </class>
+        <class name="Api.Models.User" filename="Models/User.cs" line-rate="0" branch-rate="1" complexity="1">
+          <methods />
+          <lines>
+            <line number="5" hits="0" branch="False" />
+          </lines>
+        </class>
+      </classes>
+    </package>
+  </packages>
+</coverage>"#;
+
+    #[test]
+    fn test_parse_cobertura() {
+        let report = parse_cobertura(COBERTURA.as_bytes()).unwrap();
+        assert_eq!(report.classes.len(), 2);
+
+        let service = &report.classes[0];
+        assert_eq!(service.name, "Api.Services.UserService");
+        assert_eq!(service.namespace(), "Api.Services");
+        assert_eq!(service.file, "/src/Api/Services/UserService.cs");
+        assert_eq!(
+            report.by_simple_name()["UserService"],
+            CoverageCounts { lines_covered: 4, lines_valid: 5, branches_covered: 1, branches_valid: 2 }
+        );
+
+        let totals = report.totals();
+        assert_eq!((totals.lines_covered, totals.lines_valid), (4, 6));
+        assert_eq!(totals.branch_percent(), Some(50.0));
+        assert_eq!(totals.summary(), "66.7% lines (4/6), 50.0% branches (1/2)");
+        assert_eq!(report.by_namespace().keys().collect::<Vec<_>>(), ["Api.Models", "Api.Services"]);
+        assert_eq!(report.by_file().len(), 2);
+    }
+
+    #[test]
+    fn test_merge_unions_line_hits() {
+        let mut report = parse_cobertura(COBERTURA.as_bytes()).unwrap();
+        let other = parse_cobertura(COBERTURA.replace(r#"number="12" hits="0""#, r#"number="12" hits="1""#).as_bytes()).unwrap();
+        report.merge(other);
+
+        assert_eq!(report.classes.len(), 2);
+        let counts = report.by_simple_name()["UserService"];
+        assert_eq!((counts.lines_covered, counts.lines_valid), (5, 5));
+        assert_eq!(report.classes[0].lines[&10].hits, 8);
+    }
+
+    #[test]
+    fn test_tested_class_coverage() {
+        let report = parse_cobertura(COBERTURA.as_bytes()).unwrap();
+        let index = report.by_simple_name();
+        let counts = tested_class_coverage(&index, "Api.Tests.UserServiceTests").unwrap();
+        assert_eq!(counts.line_percent(), Some(80.0));
+        assert!(tested_class_coverage(&index, "Api.Tests.OrderTests").is_none());
+        assert!(tested_class_coverage(&index, "Tests").is_none());
+    }
+}
@@ -0,0 +1,133 @@
+use super::report::{TestOutcome, TestResult};
+
+/// Parse a per-test line from `dotnet test` console output, e.g.
+/// `  Passed Calc.Tests.MathTests.Add [12 ms]`, `  Failed Divide [1 s]` or
+/// `  Skipped Later [< 1 ms]`. The name is whatever the test framework reports:
+/// fully qualified for xUnit, the bare method (plus arguments) for NUnit and MSTest.
+pub fn parse_result_line(line: &str) -> Option<TestResult> {
+    let trimmed = line.trim();
+    let (word, rest) = trimmed.split_once(' ')?;
+    let outcome = match word {
+        "Passed" => TestOutcome::Passed,
+        "Failed" => TestOutcome::Failed,
+        "Skipped" => TestOutcome::Skipped,
+        _ => return None,
+    };
+
+    let (name, duration_ms) = match rest.rsplit_once(" [") {
+        Some((name, duration)) => (name, parse_console_duration(duration.strip_suffix(']')?)?),
+        // Skipped tests are sometimes printed without a duration
+        None if outcome == TestOutcome::Skipped => (rest, 0),
+        None => return None,
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(TestResult {
+        test_name: name.to_string(),
+        outcome,
+        duration_ms,
+        error_message: None,
+        output: None,
+        test_id: None,
+    })
+}
+
+/// Parse the line testhost prints when started with `VSTEST_HOST_DEBUG=1` while it
+/// waits for a debugger: `Process Id: 12345, Name: testhost`. Returns the process id.
+pub fn parse_debug_process_id(line: &str) -> Option<u32> {
+    let rest = line.trim().strip_prefix("Process Id:")?;
+    let (pid, name) = rest.split_once(',')?;
+    let name = name.trim().strip_prefix("Name:")?.trim();
+    if !name.to_lowercase().starts_with("testhost") {
+        return None;
+    }
+    pid.trim().parse().ok()
+}
+
+/// Parse a console duration such as `12 ms`, `< 1 ms`, `3 s` or `1 m 5 s`.
+fn parse_console_duration(s: &str) -> Option<u64> {
+    let s = s.trim();
+    if s.starts_with('<') {
+        return Some(0);
+    }
+    let mut total = 0u64;
+    let mut tokens = s.split_whitespace();
+    while let Some(value) = tokens.next() {
+        let value: u64 = value.parse().ok()?;
+        let unit_ms = match tokens.next()? {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            _ => return None,
+        };
+        total = total.checked_add(value.checked_mul(unit_ms)?)?;
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_debug_process_id() {
+        assert_eq!(parse_debug_process_id("Process Id: 12345, Name: testhost"), Some(12345));
+        assert_eq!(parse_debug_process_id("  Process Id: 42, Name: testhost.x86"), Some(42));
+        assert_eq!(parse_debug_process_id("Process Id: 42, Name: dotnet"), None);
+        assert_eq!(parse_debug_process_id("Host debugging is enabled."), None);
+    }
+
+    #[test]
+    fn test_parse_passed_line() {
+        let result = parse_result_line("  Passed Calc.Tests.MathTests.Add [12 ms]").unwrap();
+        assert_eq!(result.test_name, "Calc.Tests.MathTests.Add");
+        assert_eq!(result.outcome, TestOutcome::Passed);
+        assert_eq!(result.duration_ms, 12);
+    }
+
+    #[test]
+    fn test_parse_failed_line_with_arguments() {
+        let result = parse_result_line("  Failed Divide(a: 1, b: [0]) [3 s]").unwrap();
+        assert_eq!(result.test_name, "Divide(a: 1, b: [0])");
+        assert_eq!(result.outcome, TestOutcome::Failed);
+        assert_eq!(result.duration_ms, 3000);
+    }
+
+    #[test]
+    fn test_parse_skipped_lines() {
+        let result = parse_result_line("  Skipped Later [< 1 ms]").unwrap();
+        assert_eq!(result.outcome, TestOutcome::Skipped);
+        assert_eq!(result.duration_ms, 0);
+
+        let result = parse_result_line("  Skipped Later").unwrap();
+        assert_eq!(result.test_name, "Later");
+    }
+
+    #[test]
+    fn test_parse_ignores_summary_and_other_lines() {
+        assert!(parse_result_line("Passed!  - Failed:     0, Passed:    12, Skipped:     0, Total:    12").is_none());
+        assert!(parse_result_line("Failed!  - Failed:     1, Passed:    11").is_none());
+        assert!(parse_result_line("  Failed Divide").is_none());
+        assert!(parse_result_line("Build succeeded.").is_none());
+        assert!(parse_result_line("").is_none());
+    }
+
+    #[test]
+    fn test_parse_console_duration() {
+        assert_eq!(parse_console_duration("< 1 ms"), Some(0));
+        assert_eq!(parse_console_duration("250 ms"), Some(250));
+        assert_eq!(parse_console_duration("1 m 5 s"), Some(65_000));
+        assert_eq!(parse_console_duration("soon"), None);
+    }
+
+    #[test]
+    fn test_parse_console_duration_overflow() {
+        assert_eq!(parse_console_duration("18446744073709551615 h"), None);
+        assert_eq!(parse_console_duration("18446744073709551615 ms 1 ms"), None);
+        assert!(parse_result_line("  Passed Slow [18446744073709551615 h]").is_none());
+    }
+}
@@ -187,6 +187,19 @@ pub fn build_test_name_map(project_dir: &Path) -> HashMap<String, Vec<TestMethod
     map
 }
 
+/// Test methods declared in a single C# file. Unreadable or unparsable files yield none.
+pub fn parse_test_file(path: &Path) -> Vec<TestMethodInfo> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_c_sharp::LANGUAGE;
+    if parser.set_language(&language.into()).is_err() {
+        return Vec::new();
+    }
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| parse_content_with_parser(&mut parser, &content).ok())
+        .unwrap_or_default()
+}
+
 fn glob_cs_files(dir: &Path) -> Result<Vec<std::path::PathBuf>, std::io::Error> {
     let mut files = Vec::new();
     glob_cs_files_recursive(dir, &mut files)?;
@@ -213,10 +226,56 @@ fn glob_cs_files_recursive(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> R
     Ok(())
 }
 
+/// Classes declared in a C# file, going by convention: the file name
+/// (`UserService.cs`, or `UserService.Validation.cs` for a partial class) and
+/// the test classes found in it.
+pub fn declared_class_names(path: &Path) -> Vec<String> {
+    let mut names: Vec<String> = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('.').next())
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .into_iter()
+        .collect();
+    for test in parse_test_file(path) {
+        if !names.contains(&test.class_name) {
+            names.push(test.class_name);
+        }
+    }
+    names
+}
+
+/// The class a test class tests, going by name: `UserServiceTests` (or
+/// `UserServiceTest`, `UserServiceSpecs`, `UserServiceSpec`) tests `UserService`.
+pub fn tested_class_names(test_class: &str) -> impl Iterator<Item = &str> {
+    let simple = test_class.rsplit('.').next().unwrap_or(test_class);
+    ["Tests", "Test", "Specs", "Spec"]
+        .into_iter()
+        .filter_map(move |suffix| simple.strip_suffix(suffix))
+        .filter(|name| !name.is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_declared_class_names() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("UserServiceTests.cs");
+        std::fs::write(&path, "namespace Api.Tests;\npublic class UserServiceTests {\n    [Fact]\n    public void Creates() {}\n}\npublic class UserValidatorTests {\n    [Fact]\n    public void Validates() {}\n}\n").unwrap();
+        assert_eq!(declared_class_names(&path), vec!["UserServiceTests", "UserValidatorTests"]);
+        assert_eq!(declared_class_names(Path::new("src/UserService.Validation.cs")), vec!["UserService"]);
+    }
+
+    #[test]
+    fn test_tested_class_names() {
+        assert_eq!(tested_class_names("Api.Tests.UserServiceTests").collect::<Vec<_>>(), vec!["UserService"]);
+        assert_eq!(tested_class_names("UserSpec").collect::<Vec<_>>(), vec!["User"]);
+        assert_eq!(tested_class_names("Tests").count(), 0);
+    }
+
     #[test]
     fn test_parse_xunit_test() {
         let content = r#"
@@ -355,4 +414,16 @@ namespace MyTests
         assert!(names.contains(&"HelperMethod"));
         assert!(names.contains(&"SetUp"));
     }
+
+    #[test]
+    fn test_parse_test_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("CalcTests.cs");
+        std::fs::write(&path, "namespace Calc.Tests;\npublic class CalcTests { [Fact] public void Add() {} }\n").unwrap();
+
+        let methods = parse_test_file(&path);
+        assert_eq!(methods.len(), 1);
+        assert_eq!(methods[0].full_name(), "Calc.Tests.CalcTests.Add");
+        assert!(parse_test_file(&temp_dir.path().join("Missing.cs")).is_empty());
+    }
 }
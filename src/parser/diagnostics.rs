@@ -0,0 +1,131 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// MSBuild canonical error format:
+/// `origin(line,col): error CODE: message [project]`, where the location, code and
+/// project are optional and the origin is a file, a project or a tool (`MSBUILD`, `CSC`).
+static DIAGNOSTIC_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\s*(?P<origin>\S.*?)(?:\((?P<line>\d+)(?:,(?P<col>\d+))?(?:,\d+,\d+)?\))?\s*:\s*(?P<severity>error|warning)(?:\s+(?P<code>[A-Za-z]+\d+))?\s*:\s*(?P<message>.*?)(?:\s+\[(?P<project>[^\]]+)\])?\s*$",
+    )
+    .expect("Invalid regex")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A compiler or MSBuild error or warning from build output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// e.g. `CS0103`; empty when the tool gave none
+    pub code: String,
+    pub message: String,
+    /// Source file, or the project or tool reporting the diagnostic
+    pub file: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    /// Project being built, from the trailing `[path.csproj]`
+    pub project: Option<String>,
+}
+
+impl Diagnostic {
+    /// Name of the project the diagnostic belongs to, e.g. `Api.Tests`.
+    pub fn project_name(&self) -> String {
+        let path = match &self.project {
+            Some(project) => project.as_str(),
+            None if self.file.ends_with("proj") => self.file.as_str(),
+            None => return "Build".to_string(),
+        };
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        file_name.rsplit_once('.').map_or(file_name, |(stem, _)| stem).to_string()
+    }
+
+    /// `file:line:col`, as far as the location is known.
+    pub fn location(&self) -> String {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => format!("{}:{}:{}", self.file, line, column),
+            (Some(line), None) => format!("{}:{}", self.file, line),
+            _ => self.file.clone(),
+        }
+    }
+}
+
+/// Parse an error or warning line of MSBuild output.
+pub fn parse_diagnostic(line: &str) -> Option<Diagnostic> {
+    let caps = DIAGNOSTIC_RE.captures(line)?;
+    let severity = match &caps["severity"] {
+        "error" => Severity::Error,
+        _ => Severity::Warning,
+    };
+    Some(Diagnostic {
+        severity,
+        code: caps.name("code").map_or("", |m| m.as_str()).to_string(),
+        message: caps["message"].to_string(),
+        file: caps["origin"].trim_end().to_string(),
+        line: caps.name("line").and_then(|m| m.as_str().parse().ok()),
+        column: caps.name("col").and_then(|m| m.as_str().parse().ok()),
+        project: caps.name("project").map(|m| m.as_str().to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_compiler_error() {
+        let d = parse_diagnostic(
+            "/src/Api/Users.cs(12,17): error CS0103: The name 'usr' does not exist in the current context [/src/Api/Api.csproj]",
+        )
+        .unwrap();
+        assert_eq!(d.severity, Severity::Error);
+        assert_eq!(d.code, "CS0103");
+        assert_eq!(d.message, "The name 'usr' does not exist in the current context");
+        assert_eq!(d.file, "/src/Api/Users.cs");
+        assert_eq!((d.line, d.column), (Some(12), Some(17)));
+        assert_eq!(d.project.as_deref(), Some("/src/Api/Api.csproj"));
+        assert_eq!(d.project_name(), "Api");
+        assert_eq!(d.location(), "/src/Api/Users.cs:12:17");
+    }
+
+    #[test]
+    fn test_parse_windows_warning_with_range() {
+        let d = parse_diagnostic(
+            r"  C:\src\Api.Tests\UserTests.cs(8,5,8,20): warning CS8618: Non-nullable field 'x' is uninitialized. [C:\src\Api.Tests\Api.Tests.csproj]",
+        )
+        .unwrap();
+        assert_eq!(d.severity, Severity::Warning);
+        assert_eq!(d.file, r"C:\src\Api.Tests\UserTests.cs");
+        assert_eq!((d.line, d.column), (Some(8), Some(5)));
+        assert_eq!(d.project_name(), "Api.Tests");
+    }
+
+    #[test]
+    fn test_parse_project_and_tool_errors() {
+        let d = parse_diagnostic(
+            "/src/Api.Tests/Api.Tests.csproj : error NU1101: Unable to find package Foo. [/src/Api.Tests/Api.Tests.csproj]",
+        )
+        .unwrap();
+        assert_eq!(d.code, "NU1101");
+        assert_eq!(d.line, None);
+        assert_eq!(d.location(), "/src/Api.Tests/Api.Tests.csproj");
+
+        let d = parse_diagnostic("MSBUILD : error MSB1009: Project file does not exist.").unwrap();
+        assert_eq!(d.file, "MSBUILD");
+        assert_eq!(d.code, "MSB1009");
+        assert_eq!(d.project_name(), "Build");
+    }
+
+    #[test]
+    fn test_parse_non_diagnostics() {
+        assert!(parse_diagnostic("Build FAILED.").is_none());
+        assert!(parse_diagnostic("    2 Error(s)").is_none());
+        assert!(parse_diagnostic("  Api -> /src/Api/bin/Debug/net8.0/Api.dll").is_none());
+        assert!(parse_diagnostic("error: something went wrong").is_none());
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Deserialize;
+
+use crate::error::Result;
+
+use super::junit::parse_junit_report;
+use super::nunit::parse_nunit_report;
+use super::report::{ParseOptions, TestReport};
+use super::trx::parse_trx_report;
+use super::xunit::parse_xunit_report;
+
+/// Test result file formats testament can read, and ask `dotnet test` to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultFormat {
+    #[default]
+    Trx,
+    XUnit,
+    NUnit,
+    JUnit,
+}
+
+impl ResultFormat {
+    /// `--logger` argument that makes `dotnet test` write results to `path`.
+    /// The non-TRX loggers are NuGet packages the test project must reference
+    /// (XunitXml.TestLogger, NunitXml.TestLogger, JunitXml.TestLogger).
+    pub fn logger_arg(self, path: &Path) -> String {
+        match self {
+            ResultFormat::Trx => format!("trx;LogFileName={}", path.display()),
+            ResultFormat::XUnit => format!("xunit;LogFilePath={}", path.display()),
+            ResultFormat::NUnit => format!("nunit;LogFilePath={}", path.display()),
+            ResultFormat::JUnit => format!("junit;LogFilePath={}", path.display()),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ResultFormat::Trx => "trx",
+            _ => "xml",
+        }
+    }
+
+    /// Detect the format of a result file: `.trx` by extension, anything else
+    /// by its root element.
+    pub fn detect(path: &Path) -> Result<ResultFormat> {
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("trx")) {
+            return Ok(ResultFormat::Trx);
+        }
+        let file = File::open(path).map_err(|e| crate::error::TestamentError::FileRead {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        // The root element is near the top; don't read a large file to find it
+        Ok(sniff_format(BufReader::new(file.take(64 * 1024))))
+    }
+}
+
+/// Guess the format from the root element, defaulting to TRX.
+fn sniff_format<R: BufRead>(source: R) -> ResultFormat {
+    let mut reader = Reader::from_reader(source);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                return match e.local_name().as_ref() {
+                    b"assemblies" | b"assembly" => ResultFormat::XUnit,
+                    b"test-run" | b"test-results" => ResultFormat::NUnit,
+                    b"testsuites" | b"testsuite" => ResultFormat::JUnit,
+                    _ => ResultFormat::Trx,
+                };
+            }
+            Ok(Event::Eof) | Err(_) => return ResultFormat::Trx,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Parse a result file of the given format.
+pub fn parse_report<R: BufRead>(source: R, format: ResultFormat, options: &ParseOptions) -> Result<TestReport> {
+    match format {
+        ResultFormat::Trx => parse_trx_report(source, options),
+        ResultFormat::XUnit => parse_xunit_report(source, options),
+        ResultFormat::NUnit => parse_nunit_report(source, options),
+        ResultFormat::JUnit => parse_junit_report(source, options),
+    }
+}
+
+/// Parse a duration in (fractional) seconds, as used by the XML formats, into milliseconds.
+pub(super) fn parse_seconds(s: &str) -> u64 {
+    s.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(|secs| (secs * 1000.0).round() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_seconds("0.0123"), 12);
+        assert_eq!(parse_seconds("1.5"), 1500);
+        assert_eq!(parse_seconds("2"), 2000);
+        assert_eq!(parse_seconds(""), 0);
+        assert_eq!(parse_seconds("-1"), 0);
+        assert_eq!(parse_seconds("abc"), 0);
+    }
+
+    #[test]
+    fn test_sniff_format() {
+        assert_eq!(sniff_format(r#"<?xml version="1.0"?><assemblies>"#.as_bytes()), ResultFormat::XUnit);
+        assert_eq!(sniff_format("<!-- x --><test-run id=\"1\">".as_bytes()), ResultFormat::NUnit);
+        assert_eq!(sniff_format("<testsuites>".as_bytes()), ResultFormat::JUnit);
+        assert_eq!(sniff_format("<testsuite name=\"a\"/>".as_bytes()), ResultFormat::JUnit);
+        assert_eq!(
+            sniff_format(r#"<TestRun xmlns="http://microsoft.com/schemas/VisualStudio/TeamTest/2010">"#.as_bytes()),
+            ResultFormat::Trx
+        );
+        assert_eq!(sniff_format("".as_bytes()), ResultFormat::Trx);
+    }
+
+    #[test]
+    fn test_detect_by_extension_and_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let trx = temp_dir.path().join("results.trx");
+        fs::write(&trx, "<testsuites/>").unwrap();
+        assert_eq!(ResultFormat::detect(&trx).unwrap(), ResultFormat::Trx);
+
+        let junit = temp_dir.path().join("results.xml");
+        fs::write(&junit, "<testsuites/>").unwrap();
+        assert_eq!(ResultFormat::detect(&junit).unwrap(), ResultFormat::JUnit);
+
+        assert!(ResultFormat::detect(&temp_dir.path().join("missing.xml")).is_err());
+    }
+
+    #[test]
+    fn test_logger_arg() {
+        let path = Path::new("/tmp/out.xml");
+        assert_eq!(ResultFormat::Trx.logger_arg(Path::new("/tmp/out.trx")), "trx;LogFileName=/tmp/out.trx");
+        assert_eq!(ResultFormat::XUnit.logger_arg(path), "xunit;LogFilePath=/tmp/out.xml");
+        assert_eq!(ResultFormat::NUnit.logger_arg(path), "nunit;LogFilePath=/tmp/out.xml");
+        assert_eq!(ResultFormat::JUnit.logger_arg(path), "junit;LogFilePath=/tmp/out.xml");
+    }
+
+    #[test]
+    fn test_parse_report_dispatches_on_format() {
+        let junit = r#"<testsuites><testsuite name="S"><testcase classname="A.B" name="C"/></testsuite></testsuites>"#;
+        let report = parse_report(junit.as_bytes(), ResultFormat::JUnit, &ParseOptions::default()).unwrap();
+        assert_eq!(report.results[0].test_name, "A.B.C");
+    }
+}
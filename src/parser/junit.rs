@@ -0,0 +1,220 @@
+use std::io::BufRead;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::error::{Result, TestamentError};
+
+use super::format::parse_seconds;
+use super::report::{attr_value, CapturedText, ParseOptions, Section, TestDefinition, TestOutcome, TestReport, TestResult};
+
+/// Parse a JUnit XML results file (`dotnet test --logger junit`, or any CI tool).
+///
+/// Layout: `<testsuites><testsuite name="..."><testcase classname="..." name="..."/></testsuite></testsuites>`
+pub fn parse_junit_report<R: BufRead>(source: R, options: &ParseOptions) -> Result<TestReport> {
+    let mut reader = Reader::from_reader(source);
+    reader.config_mut().trim_text(true);
+
+    let mut report = TestReport::default();
+    let mut buf = Vec::new();
+    let mut suite = String::new();
+
+    // Current test case being parsed
+    let mut current: Option<(TestResult, TestDefinition)> = None;
+    let mut captured = CapturedText::default();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.name().as_ref() {
+                b"testsuite" => suite = suite_name(&e),
+                b"testcase" => current = parse_test_case(&e, &suite, report.results.len()),
+                b"failure" | b"error" | b"skipped" => {
+                    if let Some((result, _)) = current.as_mut() {
+                        apply_status(&e, result, &mut captured);
+                        // The element body holds the stack trace; the summary is in `message`
+                        captured.enter(Section::StackTrace);
+                    }
+                }
+                b"system-out" if current.is_some() => captured.enter(Section::Output),
+                _ => {}
+            },
+            Ok(Event::Empty(e)) => match e.name().as_ref() {
+                b"testsuite" => suite = suite_name(&e),
+                b"testcase" => {
+                    if let Some((result, definition)) = parse_test_case(&e, &suite, report.results.len()) {
+                        report.push(result, definition);
+                    }
+                }
+                b"failure" | b"error" | b"skipped" => {
+                    if let Some((result, _)) = current.as_mut() {
+                        apply_status(&e, result, &mut captured);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(e)) => captured.push_text(&e.unescape().unwrap_or_default(), options),
+            Ok(Event::CData(e)) => captured.push_text(&String::from_utf8_lossy(&e), options),
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"testcase" => {
+                    if let Some((mut result, definition)) = current.take() {
+                        captured.finish(&mut result);
+                        report.push(result, definition);
+                    }
+                    captured = CapturedText::default();
+                }
+                b"failure" | b"error" | b"skipped" | b"system-out" => captured.leave(),
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(TestamentError::ResultParse(format!(
+                    "JUnit XML parse error: {}",
+                    e
+                )));
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(report)
+}
+
+fn suite_name(e: &BytesStart) -> String {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.as_ref() == b"name")
+        .map(|a| attr_value(&a))
+        .unwrap_or_default()
+}
+
+/// Mark the current test failed or skipped from a `<failure>`, `<error>` or `<skipped>` element.
+fn apply_status(e: &BytesStart, result: &mut TestResult, captured: &mut CapturedText) {
+    result.outcome = if e.name().as_ref() == b"skipped" {
+        TestOutcome::Skipped
+    } else {
+        TestOutcome::Failed
+    };
+    if let Some(message) = e
+        .attributes()
+        .flatten()
+        .find(|a| a.key.as_ref() == b"message")
+    {
+        captured.message.push_str(&attr_value(&message));
+    }
+}
+
+/// Build a result and its definition from a `<testcase>` element. Tests pass
+/// unless a `<failure>`, `<error>` or `<skipped>` child says otherwise.
+fn parse_test_case(e: &BytesStart, suite: &str, index: usize) -> Option<(TestResult, TestDefinition)> {
+    let mut name = String::new();
+    let mut class_name = String::new();
+    let mut duration_ms = 0u64;
+
+    for attr in e.attributes().flatten() {
+        match attr.key.as_ref() {
+            b"name" => name = attr_value(&attr),
+            b"classname" => class_name = attr_value(&attr),
+            b"time" => duration_ms = parse_seconds(&attr_value(&attr)),
+            _ => {}
+        }
+    }
+
+    if name.is_empty() {
+        return None;
+    }
+
+    // `name` may already be qualified (xUnit display names) or just the method
+    let test_name = if class_name.is_empty() || name.starts_with(&format!("{}.", class_name)) {
+        name.clone()
+    } else {
+        format!("{}.{}", class_name, name)
+    };
+    let method = test_name[test_name.len() - name.len()..]
+        .split('(')
+        .next()
+        .unwrap_or_default();
+    let method_name = method.rsplit('.').next().unwrap_or(method).to_string();
+
+    let id = index.to_string();
+    Some((
+        TestResult {
+            test_name,
+            outcome: TestOutcome::Passed,
+            duration_ms,
+            error_message: None,
+            output: None,
+            test_id: Some(id.clone()),
+        },
+        TestDefinition {
+            id,
+            class_name,
+            method_name,
+            storage: suite.to_string(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <testsuites>
+          <testsuite name="Calc.Tests.dll" tests="4" failures="1" errors="1" skipped="1" time="1.3">
+            <testcase classname="Calc.Tests.MathTests" name="Add(1,2)" time="0.004">
+              <system-out><![CDATA[adding]]></system-out>
+            </testcase>
+            <testcase classname="Calc.Tests.MathTests" name="Divide" time="0.25">
+              <failure message="Expected 2 but was 3" type="AssertionException"><![CDATA[at Calc.Tests.MathTests.Divide()]]></failure>
+            </testcase>
+            <testcase classname="Calc.Tests.MathTests" name="Calc.Tests.MathTests.Crash" time="1">
+              <error message="Boom" />
+            </testcase>
+            <testcase classname="Calc.Tests.MathTests" name="Later" time="0">
+              <skipped message="Not ready" />
+            </testcase>
+          </testsuite>
+        </testsuites>"#;
+
+    #[test]
+    fn test_parse_junit_outcomes_and_durations() {
+        let report = parse_junit_report(SAMPLE.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(report.results.len(), 4);
+
+        assert_eq!(report.results[0].test_name, "Calc.Tests.MathTests.Add(1,2)");
+        assert_eq!(report.results[0].outcome, TestOutcome::Passed);
+        assert_eq!(report.results[0].duration_ms, 4);
+        assert_eq!(report.results[0].output.as_deref(), Some("adding"));
+
+        assert_eq!(report.results[1].outcome, TestOutcome::Failed);
+        assert_eq!(
+            report.results[1].error_message.as_deref(),
+            Some("Expected 2 but was 3\n\nat Calc.Tests.MathTests.Divide()")
+        );
+
+        assert_eq!(report.results[2].test_name, "Calc.Tests.MathTests.Crash");
+        assert_eq!(report.results[2].outcome, TestOutcome::Failed);
+        assert_eq!(report.results[2].error_message.as_deref(), Some("Boom"));
+
+        assert_eq!(report.results[3].outcome, TestOutcome::Skipped);
+        assert_eq!(report.results[3].error_message.as_deref(), Some("Not ready"));
+    }
+
+    #[test]
+    fn test_parse_junit_definitions() {
+        let report = parse_junit_report(SAMPLE.as_bytes(), &ParseOptions::default()).unwrap();
+        let definition = &report.definitions[report.results[0].test_id.as_deref().unwrap()];
+        assert_eq!(definition.class_name, "Calc.Tests.MathTests");
+        assert_eq!(definition.method_name, "Add");
+        assert_eq!(definition.storage, "Calc.Tests.dll");
+
+        let crash = &report.definitions[report.results[2].test_id.as_deref().unwrap()];
+        assert_eq!(crash.method_name, "Crash");
+    }
+
+    #[test]
+    fn test_parse_junit_malformed() {
+        assert!(parse_junit_report("<testsuites><testsuite".as_bytes(), &ParseOptions::default()).is_err());
+    }
+}
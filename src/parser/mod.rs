@@ -1,5 +1,19 @@
+mod blame;
+mod cobertura;
+mod console;
 pub mod csharp;
+mod diagnostics;
+mod format;
+mod junit;
+mod nunit;
+mod report;
 mod trx;
+mod xunit;
 
-pub use csharp::{build_test_name_map, TestMethodInfo};
-pub use trx::{parse_trx, TestOutcome, TestResult};
+pub use blame::{blame_report, parse_blame_sequence, Abort, BlamedTest};
+pub use cobertura::{parse_cobertura, tested_class_coverage, CoverageCounts, CoverageReport};
+pub use console::{parse_debug_process_id, parse_result_line};
+pub use csharp::{build_test_name_map, declared_class_names, parse_test_file, tested_class_names, TestMethodInfo};
+pub use diagnostics::{parse_diagnostic, Diagnostic, Severity};
+pub use format::{parse_report, ResultFormat};
+pub use report::{TestOutcome, TestResult, ParseOptions, TestReport};
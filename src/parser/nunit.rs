@@ -0,0 +1,227 @@
+use std::io::BufRead;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::error::{Result, TestamentError};
+
+use super::format::parse_seconds;
+use super::report::{attr_value, CapturedText, ParseOptions, Section, TestDefinition, TestOutcome, TestReport, TestResult};
+
+/// Parse an NUnit 3 `TestResult.xml` file (`dotnet test --logger nunit`).
+///
+/// Layout: `<test-run><test-suite type="Assembly"> ... <test-case .../> ... </test-suite></test-run>`
+pub fn parse_nunit_report<R: BufRead>(source: R, options: &ParseOptions) -> Result<TestReport> {
+    let mut reader = Reader::from_reader(source);
+    reader.config_mut().trim_text(true);
+
+    let mut report = TestReport::default();
+    let mut buf = Vec::new();
+    let mut assembly = String::new();
+
+    // Current test case being parsed
+    let mut current: Option<(TestResult, TestDefinition)> = None;
+    let mut in_failure = false;
+    let mut in_reason = false;
+    let mut captured = CapturedText::default();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.name().as_ref() {
+                b"test-suite" => {
+                    if let Some(path) = assembly_path(&e) {
+                        assembly = path;
+                    }
+                }
+                b"test-case" => current = parse_test_case(&e, &assembly, report.results.len()),
+                b"failure" if current.is_some() => in_failure = true,
+                b"reason" if current.is_some() => in_reason = true,
+                // Assertion messages repeat the failure message, so only read it there
+                b"message" if in_failure || in_reason => captured.enter(Section::Message),
+                b"stack-trace" if in_failure => captured.enter(Section::StackTrace),
+                b"output" if current.is_some() => captured.enter(Section::Output),
+                _ => {}
+            },
+            Ok(Event::Empty(e)) => match e.name().as_ref() {
+                b"test-case" => {
+                    if let Some((result, definition)) = parse_test_case(&e, &assembly, report.results.len()) {
+                        report.push(result, definition);
+                    }
+                }
+                b"test-suite" => {
+                    if let Some(path) = assembly_path(&e) {
+                        assembly = path;
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(e)) => captured.push_text(&e.unescape().unwrap_or_default(), options),
+            Ok(Event::CData(e)) => captured.push_text(&String::from_utf8_lossy(&e), options),
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"test-case" => {
+                    if let Some((mut result, definition)) = current.take() {
+                        captured.finish(&mut result);
+                        report.push(result, definition);
+                    }
+                    captured = CapturedText::default();
+                }
+                b"failure" => in_failure = false,
+                b"reason" => in_reason = false,
+                b"message" | b"stack-trace" | b"output" => captured.leave(),
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(TestamentError::ResultParse(format!(
+                    "NUnit XML parse error: {}",
+                    e
+                )));
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(report)
+}
+
+/// Assembly path of a `<test-suite type="Assembly">` element (`fullname`, else `name`).
+fn assembly_path(e: &BytesStart) -> Option<String> {
+    let mut is_assembly = false;
+    let mut name = None;
+    let mut fullname = None;
+    for attr in e.attributes().flatten() {
+        match attr.key.as_ref() {
+            b"type" => is_assembly = attr.value.as_ref() == b"Assembly",
+            b"name" => name = Some(attr_value(&attr)),
+            b"fullname" => fullname = Some(attr_value(&attr)),
+            _ => {}
+        }
+    }
+    if is_assembly {
+        fullname.or(name)
+    } else {
+        None
+    }
+}
+
+/// Build a result and its definition from a `<test-case>` element.
+fn parse_test_case(e: &BytesStart, assembly: &str, index: usize) -> Option<(TestResult, TestDefinition)> {
+    let mut name = String::new();
+    let mut fullname = String::new();
+    let mut class_name = String::new();
+    let mut method_name = String::new();
+    let mut outcome = TestOutcome::Skipped;
+    let mut duration_ms = 0u64;
+
+    for attr in e.attributes().flatten() {
+        match attr.key.as_ref() {
+            b"name" => name = attr_value(&attr),
+            b"fullname" => fullname = attr_value(&attr),
+            b"classname" => class_name = attr_value(&attr),
+            b"methodname" => method_name = attr_value(&attr),
+            b"result" => {
+                // Inconclusive and Warning results didn't pass or fail
+                outcome = match attr_value(&attr).as_str() {
+                    "Passed" => TestOutcome::Passed,
+                    "Failed" => TestOutcome::Failed,
+                    _ => TestOutcome::Skipped,
+                };
+            }
+            b"duration" => duration_ms = parse_seconds(&attr_value(&attr)),
+            _ => {}
+        }
+    }
+
+    let test_name = if fullname.is_empty() { name } else { fullname };
+    if test_name.is_empty() {
+        return None;
+    }
+
+    let id = index.to_string();
+    Some((
+        TestResult {
+            test_name,
+            outcome,
+            duration_ms,
+            error_message: None,
+            output: None,
+            test_id: Some(id.clone()),
+        },
+        TestDefinition {
+            id,
+            class_name,
+            method_name,
+            storage: assembly.to_string(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <test-run id="2" testcasecount="3" result="Failed">
+          <test-suite type="Assembly" name="Calc.Tests.dll" fullname="/src/Calc.Tests/bin/Debug/net8.0/Calc.Tests.dll">
+            <test-suite type="TestFixture" name="MathTests" fullname="Calc.Tests.MathTests">
+              <test-case id="0-1001" name="Add(1,2)" fullname="Calc.Tests.MathTests.Add(1,2)" methodname="Add" classname="Calc.Tests.MathTests" result="Passed" duration="0.004">
+                <output><![CDATA[adding]]></output>
+              </test-case>
+              <test-case id="0-1002" name="Divide" fullname="Calc.Tests.MathTests.Divide" methodname="Divide" classname="Calc.Tests.MathTests" result="Failed" duration="0.250">
+                <failure>
+                  <message><![CDATA[Expected 2 but was 3]]></message>
+                  <stack-trace><![CDATA[at Calc.Tests.MathTests.Divide()]]></stack-trace>
+                </failure>
+                <assertions>
+                  <assertion result="Failed"><message><![CDATA[Expected 2 but was 3]]></message></assertion>
+                </assertions>
+              </test-case>
+              <test-case id="0-1003" name="Later" fullname="Calc.Tests.MathTests.Later" methodname="Later" classname="Calc.Tests.MathTests" result="Skipped" duration="0">
+                <reason><message><![CDATA[Not ready]]></message></reason>
+              </test-case>
+            </test-suite>
+          </test-suite>
+        </test-run>"#;
+
+    #[test]
+    fn test_parse_nunit_outcomes_and_durations() {
+        let report = parse_nunit_report(SAMPLE.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(report.results.len(), 3);
+
+        assert_eq!(report.results[0].test_name, "Calc.Tests.MathTests.Add(1,2)");
+        assert_eq!(report.results[0].outcome, TestOutcome::Passed);
+        assert_eq!(report.results[0].duration_ms, 4);
+        assert_eq!(report.results[0].output.as_deref(), Some("adding"));
+
+        assert_eq!(report.results[1].outcome, TestOutcome::Failed);
+        assert_eq!(report.results[1].duration_ms, 250);
+        assert_eq!(
+            report.results[1].error_message.as_deref(),
+            Some("Expected 2 but was 3\n\nat Calc.Tests.MathTests.Divide()")
+        );
+
+        assert_eq!(report.results[2].outcome, TestOutcome::Skipped);
+        assert_eq!(report.results[2].error_message.as_deref(), Some("Not ready"));
+    }
+
+    #[test]
+    fn test_parse_nunit_definitions() {
+        let report = parse_nunit_report(SAMPLE.as_bytes(), &ParseOptions::default()).unwrap();
+        let definition = &report.definitions[report.results[0].test_id.as_deref().unwrap()];
+        assert_eq!(definition.class_name, "Calc.Tests.MathTests");
+        assert_eq!(definition.method_name, "Add");
+        assert_eq!(definition.storage, "/src/Calc.Tests/bin/Debug/net8.0/Calc.Tests.dll");
+        assert_eq!(
+            report.qualified_name(&report.results[0]).as_deref(),
+            Some("Calc.Tests.MathTests.Add(1,2)")
+        );
+    }
+
+    #[test]
+    fn test_parse_nunit_inconclusive_is_skipped() {
+        let xml = r#"<test-run><test-case name="T" fullname="A.T" result="Inconclusive" /></test-run>"#;
+        let report = parse_nunit_report(xml.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(report.results[0].outcome, TestOutcome::Skipped);
+    }
+}
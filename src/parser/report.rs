@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use quick_xml::events::attributes::Attribute;
+
+/// Default cap on captured stdout kept per test (64 KiB).
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub test_name: String,
+    pub outcome: TestOutcome,
+    pub duration_ms: u64,
+    pub error_message: Option<String>,
+    pub output: Option<String>,
+    /// Key of the result's entry in `TestReport::definitions` (the TRX `testId`)
+    pub test_id: Option<String>,
+}
+
+/// Where a test is declared. For TRX this is a `UnitTest` entry from the
+/// `TestDefinitions` section; other formats synthesize one per result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestDefinition {
+    pub id: String,
+    /// Declaring class, e.g. `Namespace.ClassName`
+    pub class_name: String,
+    /// Method name without arguments
+    pub method_name: String,
+    /// Path of the test assembly the test was loaded from
+    pub storage: String,
+}
+
+/// Results and test definitions read from a result file.
+#[derive(Debug, Clone, Default)]
+pub struct TestReport {
+    pub results: Vec<TestResult>,
+    /// Test definitions keyed by test id
+    pub definitions: HashMap<String, TestDefinition>,
+}
+
+impl TestReport {
+    /// Add a result together with the definition synthesized for it (formats
+    /// other than TRX have no separate definitions section).
+    pub(super) fn push(&mut self, result: TestResult, definition: TestDefinition) {
+        self.definitions.insert(definition.id.clone(), definition);
+        self.results.push(result);
+    }
+
+    /// Append the results of another report, e.g. from a later batch of the same run.
+    /// Definitions whose ids clash are re-keyed: formats without test ids number
+    /// their synthesized definitions from 1 in every file.
+    pub fn merge(&mut self, other: TestReport) {
+        let mut renamed = HashMap::new();
+        for (id, mut definition) in other.definitions {
+            if self.definitions.contains_key(&id) {
+                let new_id = (1..)
+                    .map(|n| format!("{}-{}", id, n))
+                    .find(|candidate| !self.definitions.contains_key(candidate))
+                    .unwrap_or_default();
+                definition.id = new_id.clone();
+                renamed.insert(id, new_id.clone());
+                self.definitions.insert(new_id, definition);
+            } else {
+                self.definitions.insert(id, definition);
+            }
+        }
+        for mut result in other.results {
+            if let Some(new_id) = result.test_id.as_ref().and_then(|id| renamed.get(id)) {
+                result.test_id = Some(new_id.clone());
+            }
+            self.results.push(result);
+        }
+    }
+
+    /// Resolve a result to its fully-qualified name (`Namespace.Class.Method`, plus
+    /// any parameter list) using its test definition. Returns None when the result
+    /// has no matching definition.
+    pub fn qualified_name(&self, result: &TestResult) -> Option<String> {
+        let definition = self.definitions.get(result.test_id.as_deref()?)?;
+        if definition.class_name.is_empty() {
+            return None;
+        }
+        let class_prefix = format!("{}.", definition.class_name);
+        if result.test_name.starts_with(&class_prefix) {
+            // xUnit style: testName is already fully qualified
+            Some(result.test_name.clone())
+        } else if result.test_name == definition.method_name
+            || result.test_name.starts_with(&format!("{}(", definition.method_name))
+        {
+            // NUnit/MSTest style: bare method name, possibly with arguments
+            Some(format!("{}{}", class_prefix, result.test_name))
+        } else {
+            // Custom display name - fall back to the declaring method
+            Some(format!("{}{}", class_prefix, definition.method_name))
+        }
+    }
+}
+
+/// Options controlling how much of a result file is kept in memory.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Keep the `<StdOut>` captured for each test
+    pub capture_output: bool,
+    /// Truncate captured stdout beyond this many bytes (None keeps everything)
+    pub max_output_bytes: Option<usize>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            capture_output: true,
+            max_output_bytes: Some(DEFAULT_MAX_OUTPUT_BYTES),
+        }
+    }
+}
+
+/// Error and output text collected for the result currently being parsed.
+#[derive(Default)]
+pub(super) struct CapturedText {
+    pub(super) message: String,
+    pub(super) stack_trace: String,
+    output: String,
+    output_dropped: usize,
+    section: Option<Section>,
+}
+
+/// Part of a result that the element being read holds text for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Section {
+    Message,
+    StackTrace,
+    Output,
+}
+
+impl CapturedText {
+    /// Collect text into `section` until `leave`.
+    pub(super) fn enter(&mut self, section: Section) {
+        self.section = Some(section);
+    }
+
+    pub(super) fn leave(&mut self) {
+        self.section = None;
+    }
+
+    /// Append text of the element being read to the section it was entered for.
+    /// Text outside a section is ignored.
+    pub(super) fn push_text(&mut self, text: &str, options: &ParseOptions) {
+        match self.section {
+            Some(Section::Message) => self.message.push_str(text),
+            Some(Section::StackTrace) => self.stack_trace.push_str(text),
+            Some(Section::Output) => self.push_output(text, options),
+            None => {}
+        }
+    }
+
+    /// Append captured stdout, honouring the capture options.
+    fn push_output(&mut self, text: &str, options: &ParseOptions) {
+        if options.capture_output {
+            push_capped(&mut self.output, text, options.max_output_bytes, &mut self.output_dropped);
+        }
+    }
+
+    /// Move the collected text into `result`. The error message and stack trace
+    /// are combined into a single error.
+    pub(super) fn finish(&mut self, result: &mut TestResult) {
+        if !self.message.is_empty() || !self.stack_trace.is_empty() {
+            let mut full_error = self.message.trim().to_string();
+            if !self.stack_trace.is_empty() {
+                if !full_error.is_empty() {
+                    full_error.push_str("\n\n");
+                }
+                full_error.push_str(self.stack_trace.trim());
+            }
+            result.error_message = Some(full_error);
+        }
+        if !self.output.is_empty() {
+            let mut output = std::mem::take(&mut self.output);
+            if self.output_dropped > 0 {
+                output.push_str(&format!("\n... ({} bytes truncated)", self.output_dropped));
+            }
+            result.output = Some(output);
+        }
+    }
+}
+
+/// Unescaped attribute value (parameterized test names contain `&quot;` etc.)
+pub(super) fn attr_value(attr: &Attribute) -> String {
+    attr.unescape_value()
+        .map(|v| v.into_owned())
+        .unwrap_or_else(|_| String::from_utf8_lossy(&attr.value).to_string())
+}
+
+/// Append `text` to `dst` without letting `dst` grow past `limit` bytes.
+/// Bytes that don't fit are counted in `dropped`.
+fn push_capped(dst: &mut String, text: &str, limit: Option<usize>, dropped: &mut usize) {
+    let Some(limit) = limit else {
+        dst.push_str(text);
+        return;
+    };
+    let room = limit.saturating_sub(dst.len());
+    if text.len() <= room {
+        dst.push_str(text);
+        return;
+    }
+    // Cut on a char boundary so the kept prefix stays valid UTF-8
+    let mut cut = room;
+    while cut > 0 && !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    dst.push_str(&text[..cut]);
+    *dropped += text.len() - cut;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TestOutcome tests
+    #[test]
+    fn test_outcome_equality() {
+        assert_eq!(TestOutcome::Passed, TestOutcome::Passed);
+        assert_eq!(TestOutcome::Failed, TestOutcome::Failed);
+        assert_eq!(TestOutcome::Skipped, TestOutcome::Skipped);
+    }
+
+    #[test]
+    fn test_outcome_inequality() {
+        assert_ne!(TestOutcome::Passed, TestOutcome::Failed);
+        assert_ne!(TestOutcome::Failed, TestOutcome::Skipped);
+        assert_ne!(TestOutcome::Passed, TestOutcome::Skipped);
+    }
+
+    #[test]
+    fn test_outcome_clone() {
+        let outcome = TestOutcome::Passed;
+        let cloned = outcome.clone();
+        assert_eq!(outcome, cloned);
+    }
+
+    #[test]
+    fn test_outcome_debug() {
+        assert_eq!(format!("{:?}", TestOutcome::Passed), "Passed");
+        assert_eq!(format!("{:?}", TestOutcome::Failed), "Failed");
+        assert_eq!(format!("{:?}", TestOutcome::Skipped), "Skipped");
+    }
+
+    #[test]
+    fn test_push_capped_respects_char_boundaries() {
+        let mut dst = String::new();
+        let mut dropped = 0;
+        push_capped(&mut dst, "aé", Some(2), &mut dropped);
+        assert_eq!(dst, "a");
+        assert_eq!(dropped, 2);
+    }
+
+    fn report_with_definition(test_name: &str, class_name: &str, method_name: &str) -> (TestReport, TestResult) {
+        let result = TestResult {
+            test_name: test_name.to_string(),
+            outcome: TestOutcome::Passed,
+            duration_ms: 0,
+            error_message: None,
+            output: None,
+            test_id: Some("id".to_string()),
+        };
+        let mut report = TestReport::default();
+        report.definitions.insert("id".to_string(), TestDefinition {
+            id: "id".to_string(),
+            class_name: class_name.to_string(),
+            method_name: method_name.to_string(),
+            storage: String::new(),
+        });
+        (report, result)
+    }
+
+    #[test]
+    fn test_qualified_name_already_qualified() {
+        let (report, result) = report_with_definition("NS.Calc.Add(a: 1)", "NS.Calc", "Add");
+        assert_eq!(report.qualified_name(&result).as_deref(), Some("NS.Calc.Add(a: 1)"));
+    }
+
+    #[test]
+    fn test_qualified_name_bare_method_with_args() {
+        let (report, result) = report_with_definition("Add(1,2)", "NS.Calc", "Add");
+        assert_eq!(report.qualified_name(&result).as_deref(), Some("NS.Calc.Add(1,2)"));
+    }
+
+    #[test]
+    fn test_qualified_name_custom_display_name() {
+        let (report, result) = report_with_definition("Adds two numbers", "NS.Calc", "Add");
+        assert_eq!(report.qualified_name(&result).as_deref(), Some("NS.Calc.Add"));
+    }
+
+    #[test]
+    fn test_merge_rekeys_clashing_definitions() {
+        let (mut report, result) = report_with_definition("Add", "NS.Calc", "Add");
+        report.results.push(result);
+        let (mut other, result) = report_with_definition("Sub", "NS.Calc", "Sub");
+        other.results.push(result);
+
+        report.merge(other);
+        assert_eq!(report.results.len(), 2);
+        assert_eq!(report.definitions.len(), 2);
+        assert_eq!(report.qualified_name(&report.results[0]).as_deref(), Some("NS.Calc.Add"));
+        assert_eq!(report.qualified_name(&report.results[1]).as_deref(), Some("NS.Calc.Sub"));
+        assert_eq!(report.results[1].test_id.as_deref(), Some("id-1"));
+    }
+
+    #[test]
+    fn test_qualified_name_without_definition() {
+        let (report, mut result) = report_with_definition("Add", "NS.Calc", "Add");
+        result.test_id = Some("other".to_string());
+        assert!(report.qualified_name(&result).is_none());
+    }
+}
@@ -1,148 +1,82 @@
-use quick_xml::events::Event;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 
 use crate::error::{Result, TestamentError};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum TestOutcome {
-    Passed,
-    Failed,
-    Skipped,
-}
+use super::report::{attr_value, CapturedText, ParseOptions, Section, TestDefinition, TestOutcome, TestReport, TestResult};
 
-#[derive(Debug, Clone)]
-pub struct TestResult {
-    pub test_name: String,
-    pub outcome: TestOutcome,
-    pub duration_ms: u64,
-    pub error_message: Option<String>,
+/// Parse TRX content held in memory. Convenience wrapper over `parse_trx_report`.
+#[cfg(test)]
+pub fn parse_trx(content: &str) -> Result<Vec<TestResult>> {
+    Ok(parse_trx_report(content.as_bytes(), &ParseOptions::default())?.results)
 }
 
-pub fn parse_trx(content: &str) -> Result<Vec<TestResult>> {
-    let mut reader = Reader::from_str(content);
+/// Parse results and test definitions of a TRX file from a buffered reader.
+///
+/// Only one `UnitTestResult` is held at a time, so memory use is bounded by the
+/// number of results rather than the size of the file (captured stdout is the
+/// bulk of large TRX files and is truncated per `options`).
+pub fn parse_trx_report<R: BufRead>(source: R, options: &ParseOptions) -> Result<TestReport> {
+    let mut reader = Reader::from_reader(source);
     reader.config_mut().trim_text(true);
 
     let mut results = Vec::new();
+    let mut definitions = HashMap::new();
     let mut buf = Vec::new();
 
+    // Current test definition being parsed
+    let mut current_definition: Option<TestDefinition> = None;
+
     // Current test being parsed
     let mut current_test: Option<TestResult> = None;
     let mut in_error_info = false;
-    let mut in_message = false;
-    let mut in_stack_trace = false;
-    let mut error_message = String::new();
-    let mut stack_trace = String::new();
+    let mut captured = CapturedText::default();
 
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(e)) => {
                 match e.name().as_ref() {
-                    b"UnitTestResult" => {
-                        let mut test_name = String::new();
-                        let mut outcome = TestOutcome::Passed;
-                        let mut duration_ms = 0u64;
-
-                        for attr in e.attributes().flatten() {
-                            match attr.key.as_ref() {
-                                b"testName" => {
-                                    test_name = String::from_utf8_lossy(&attr.value).to_string();
-                                }
-                                b"outcome" => {
-                                    let val = String::from_utf8_lossy(&attr.value);
-                                    outcome = match val.as_ref() {
-                                        "Passed" => TestOutcome::Passed,
-                                        "Failed" => TestOutcome::Failed,
-                                        _ => TestOutcome::Skipped,
-                                    };
-                                }
-                                b"duration" => {
-                                    let val = String::from_utf8_lossy(&attr.value);
-                                    duration_ms = parse_duration(&val);
-                                }
-                                _ => {}
-                            }
-                        }
-
-                        if !test_name.is_empty() {
-                            current_test = Some(TestResult {
-                                test_name,
-                                outcome,
-                                duration_ms,
-                                error_message: None,
-                            });
-                        }
-                    }
+                    b"UnitTestResult" => current_test = parse_result_attributes(&e),
                     b"ErrorInfo" => in_error_info = true,
-                    b"Message" if in_error_info => in_message = true,
-                    b"StackTrace" if in_error_info => in_stack_trace = true,
+                    b"Message" if in_error_info => captured.enter(Section::Message),
+                    b"StackTrace" if in_error_info => captured.enter(Section::StackTrace),
+                    b"StdOut" if current_test.is_some() => captured.enter(Section::Output),
+                    b"UnitTest" => current_definition = parse_definition_attributes(&e),
+                    b"TestMethod" => apply_test_method(&e, current_definition.as_mut()),
                     _ => {}
                 }
             }
-            Ok(Event::Empty(e)) if e.name().as_ref() == b"UnitTestResult" => {
-                let mut test_name = String::new();
-                let mut outcome = TestOutcome::Passed;
-                let mut duration_ms = 0u64;
-
-                for attr in e.attributes().flatten() {
-                    match attr.key.as_ref() {
-                        b"testName" => {
-                            test_name = String::from_utf8_lossy(&attr.value).to_string();
-                        }
-                        b"outcome" => {
-                            let val = String::from_utf8_lossy(&attr.value);
-                            outcome = match val.as_ref() {
-                                "Passed" => TestOutcome::Passed,
-                                "Failed" => TestOutcome::Failed,
-                                _ => TestOutcome::Skipped,
-                            };
-                        }
-                        b"duration" => {
-                            let val = String::from_utf8_lossy(&attr.value);
-                            duration_ms = parse_duration(&val);
+            Ok(Event::Empty(e)) => {
+                match e.name().as_ref() {
+                    b"UnitTestResult" => {
+                        if let Some(test) = parse_result_attributes(&e) {
+                            results.push(test);
                         }
-                        _ => {}
                     }
-                }
-
-                if !test_name.is_empty() {
-                    results.push(TestResult {
-                        test_name,
-                        outcome,
-                        duration_ms,
-                        error_message: None,
-                    });
-                }
-            }
-            Ok(Event::Text(e)) => {
-                if in_message {
-                    error_message.push_str(&e.unescape().unwrap_or_default());
-                } else if in_stack_trace {
-                    stack_trace.push_str(&e.unescape().unwrap_or_default());
+                    b"TestMethod" => apply_test_method(&e, current_definition.as_mut()),
+                    _ => {}
                 }
             }
+            Ok(Event::Text(e)) => captured.push_text(&e.unescape().unwrap_or_default(), options),
             Ok(Event::End(e)) => {
                 match e.name().as_ref() {
                     b"UnitTestResult" => {
                         if let Some(mut test) = current_test.take() {
-                            // Combine error message and stack trace
-                            if !error_message.is_empty() || !stack_trace.is_empty() {
-                                let mut full_error = error_message.trim().to_string();
-                                if !stack_trace.is_empty() {
-                                    if !full_error.is_empty() {
-                                        full_error.push_str("\n\n");
-                                    }
-                                    full_error.push_str(stack_trace.trim());
-                                }
-                                test.error_message = Some(full_error);
-                            }
+                            captured.finish(&mut test);
                             results.push(test);
-                            error_message.clear();
-                            stack_trace.clear();
                         }
+                        captured = CapturedText::default();
                     }
                     b"ErrorInfo" => in_error_info = false,
-                    b"Message" => in_message = false,
-                    b"StackTrace" => in_stack_trace = false,
+                    b"Message" | b"StackTrace" | b"StdOut" => captured.leave(),
+                    b"UnitTest" => {
+                        if let Some(definition) = current_definition.take() {
+                            definitions.insert(definition.id.clone(), definition);
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -158,10 +92,100 @@ pub fn parse_trx(content: &str) -> Result<Vec<TestResult>> {
         buf.clear();
     }
 
-    Ok(results)
+    Ok(TestReport { results, definitions })
+}
+
+/// Build a result from the attributes of a `UnitTestResult` element.
+/// Returns None when the element has no `testName`.
+fn parse_result_attributes(e: &BytesStart) -> Option<TestResult> {
+    let mut test_name = String::new();
+    let mut outcome = TestOutcome::Passed;
+    let mut duration_ms = 0u64;
+    let mut test_id = None;
+
+    for attr in e.attributes().flatten() {
+        match attr.key.as_ref() {
+            b"testName" => {
+                test_name = attr_value(&attr);
+            }
+            b"testId" => {
+                test_id = Some(attr_value(&attr));
+            }
+            b"outcome" => {
+                let val = String::from_utf8_lossy(&attr.value);
+                outcome = match val.as_ref() {
+                    "Passed" => TestOutcome::Passed,
+                    "Failed" => TestOutcome::Failed,
+                    _ => TestOutcome::Skipped,
+                };
+            }
+            b"duration" => {
+                let val = String::from_utf8_lossy(&attr.value);
+                duration_ms = parse_duration(&val);
+            }
+            _ => {}
+        }
+    }
+
+    if test_name.is_empty() {
+        return None;
+    }
+
+    Some(TestResult {
+        test_name,
+        outcome,
+        duration_ms,
+        error_message: None,
+        output: None,
+        test_id,
+    })
 }
 
-fn parse_duration(s: &str) -> u64 {
+/// Start a definition from the attributes of a `UnitTest` element.
+/// Returns None when the element has no `id`.
+fn parse_definition_attributes(e: &BytesStart) -> Option<TestDefinition> {
+    let mut id = String::new();
+    let mut storage = String::new();
+    let mut method_name = String::new();
+
+    for attr in e.attributes().flatten() {
+        match attr.key.as_ref() {
+            b"id" => id = attr_value(&attr),
+            b"storage" => storage = attr_value(&attr),
+            b"name" => method_name = attr_value(&attr),
+            _ => {}
+        }
+    }
+
+    if id.is_empty() {
+        return None;
+    }
+
+    Some(TestDefinition {
+        id,
+        class_name: String::new(),
+        method_name,
+        storage,
+    })
+}
+
+/// Fill in class and method name from a `TestMethod` element.
+fn apply_test_method(e: &BytesStart, definition: Option<&mut TestDefinition>) {
+    let Some(definition) = definition else { return };
+    for attr in e.attributes().flatten() {
+        match attr.key.as_ref() {
+            b"className" => {
+                // MSTest writes assembly-qualified names: "NS.Class, Assembly, Version=..."
+                let value = attr_value(&attr);
+                definition.class_name = value.split(',').next().unwrap_or("").trim().to_string();
+            }
+            b"name" => definition.method_name = attr_value(&attr),
+            _ => {}
+        }
+    }
+}
+
+pub(super) fn parse_duration(s: &str) -> u64 {
     // Format: HH:MM:SS.FFFFFFF
     let parts: Vec<&str> = s.split(':').collect();
     if parts.len() != 3 {
@@ -192,35 +216,6 @@ fn parse_duration(s: &str) -> u64 {
 mod tests {
     use super::*;
 
-    // TestOutcome tests
-    #[test]
-    fn test_outcome_equality() {
-        assert_eq!(TestOutcome::Passed, TestOutcome::Passed);
-        assert_eq!(TestOutcome::Failed, TestOutcome::Failed);
-        assert_eq!(TestOutcome::Skipped, TestOutcome::Skipped);
-    }
-
-    #[test]
-    fn test_outcome_inequality() {
-        assert_ne!(TestOutcome::Passed, TestOutcome::Failed);
-        assert_ne!(TestOutcome::Failed, TestOutcome::Skipped);
-        assert_ne!(TestOutcome::Passed, TestOutcome::Skipped);
-    }
-
-    #[test]
-    fn test_outcome_clone() {
-        let outcome = TestOutcome::Passed;
-        let cloned = outcome.clone();
-        assert_eq!(outcome, cloned);
-    }
-
-    #[test]
-    fn test_outcome_debug() {
-        assert_eq!(format!("{:?}", TestOutcome::Passed), "Passed");
-        assert_eq!(format!("{:?}", TestOutcome::Failed), "Failed");
-        assert_eq!(format!("{:?}", TestOutcome::Skipped), "Skipped");
-    }
-
     // parse_duration tests
     #[test]
     fn test_parse_duration_zero() {
@@ -519,6 +514,8 @@ mod tests {
             outcome: TestOutcome::Passed,
             duration_ms: 100,
             error_message: Some("error".to_string()),
+            output: None,
+            test_id: None,
         };
 
         let cloned = result.clone();
@@ -535,10 +532,103 @@ mod tests {
             outcome: TestOutcome::Failed,
             duration_ms: 100,
             error_message: None,
+            output: None,
+            test_id: None,
         };
 
         let debug_str = format!("{:?}", result);
         assert!(debug_str.contains("Test1"));
         assert!(debug_str.contains("Failed"));
     }
+
+    #[test]
+    fn test_parse_trx_captures_std_out() {
+        let xml = r#"<TestRun>
+                <Results>
+                    <UnitTestResult testName="Test1" outcome="Passed" duration="00:00:00.1000000">
+                        <Output>
+                            <StdOut>hello from the test</StdOut>
+                        </Output>
+                    </UnitTestResult>
+                </Results>
+            </TestRun>"#;
+
+        let result = parse_trx(xml).unwrap();
+        assert_eq!(result[0].output.as_deref(), Some("hello from the test"));
+        assert!(result[0].error_message.is_none());
+    }
+
+    #[test]
+    fn test_parse_trx_report_truncates_std_out() {
+        let xml = r#"<TestRun>
+                <Results>
+                    <UnitTestResult testName="Test1" outcome="Failed">
+                        <Output>
+                            <StdOut>0123456789</StdOut>
+                            <ErrorInfo><Message>boom</Message></ErrorInfo>
+                        </Output>
+                    </UnitTestResult>
+                    <UnitTestResult testName="Test2" outcome="Passed">
+                        <Output><StdOut>abc</StdOut></Output>
+                    </UnitTestResult>
+                </Results>
+            </TestRun>"#;
+        let options = ParseOptions { capture_output: true, max_output_bytes: Some(4) };
+
+        let result = parse_trx_report(xml.as_bytes(), &options).unwrap().results;
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].output.as_deref(), Some("0123\n... (6 bytes truncated)"));
+        assert_eq!(result[0].error_message.as_deref(), Some("boom"));
+        // Truncation state must not leak into the next result
+        assert_eq!(result[1].output.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_parse_trx_report_without_output_capture() {
+        let xml = r#"<TestRun><Results>
+                <UnitTestResult testName="Test1" outcome="Passed">
+                    <Output><StdOut>noise</StdOut></Output>
+                </UnitTestResult>
+            </Results></TestRun>"#;
+        let options = ParseOptions { capture_output: false, max_output_bytes: None };
+
+        let result = parse_trx_report(xml.as_bytes(), &options).unwrap().results;
+        assert!(result[0].output.is_none());
+    }
+
+    #[test]
+    fn test_parse_trx_report_reads_definitions() {
+        let xml = r#"<TestRun>
+                <Results>
+                    <UnitTestResult testId="id-1" testName="Add(a: 1)" outcome="Passed" />
+                </Results>
+                <TestDefinitions>
+                    <UnitTest name="Add(a: 1)" storage="/src/Calc.Tests/bin/Debug/net8.0/Calc.Tests.dll" id="id-1">
+                        <Execution id="exec-1" />
+                        <TestMethod codeBase="Calc.Tests.dll" className="Calc.Tests.MathTests, Calc.Tests, Version=1.0.0.0" name="Add" />
+                    </UnitTest>
+                </TestDefinitions>
+            </TestRun>"#;
+
+        let report = parse_trx_report(xml.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(report.results.len(), 1);
+        assert_eq!(report.results[0].test_id.as_deref(), Some("id-1"));
+
+        let definition = &report.definitions["id-1"];
+        assert_eq!(definition.class_name, "Calc.Tests.MathTests");
+        assert_eq!(definition.method_name, "Add");
+        assert_eq!(definition.storage, "/src/Calc.Tests/bin/Debug/net8.0/Calc.Tests.dll");
+    }
+
+    #[test]
+    fn test_parse_trx_report_without_definitions() {
+        let xml = r#"<TestRun><Results>
+                <UnitTestResult testName="Test1" outcome="Passed" />
+            </Results></TestRun>"#;
+
+        let report = parse_trx_report(xml.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(report.results.len(), 1);
+        assert!(report.results[0].test_id.is_none());
+        assert!(report.definitions.is_empty());
+    }
 }
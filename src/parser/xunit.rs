@@ -0,0 +1,195 @@
+use std::io::BufRead;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::error::{Result, TestamentError};
+
+use super::format::parse_seconds;
+use super::report::{attr_value, CapturedText, ParseOptions, Section, TestDefinition, TestOutcome, TestReport, TestResult};
+
+/// Parse an xUnit v2 XML results file (`dotnet test --logger xunit`).
+///
+/// Layout: `<assemblies><assembly name="..."><collection><test .../></collection></assembly></assemblies>`
+pub fn parse_xunit_report<R: BufRead>(source: R, options: &ParseOptions) -> Result<TestReport> {
+    let mut reader = Reader::from_reader(source);
+    reader.config_mut().trim_text(true);
+
+    let mut report = TestReport::default();
+    let mut buf = Vec::new();
+    let mut assembly = String::new();
+
+    // Current test being parsed
+    let mut current: Option<(TestResult, TestDefinition)> = None;
+    let mut in_failure = false;
+    let mut captured = CapturedText::default();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                match e.name().as_ref() {
+                    b"assembly" => assembly = attribute(&e, b"name").unwrap_or_default(),
+                    b"test" => current = parse_test(&e, &assembly, report.results.len()),
+                    b"failure" => in_failure = true,
+                    b"message" if in_failure => captured.enter(Section::Message),
+                    b"stack-trace" if in_failure => captured.enter(Section::StackTrace),
+                    b"reason" if current.is_some() => captured.enter(Section::Message),
+                    b"output" if current.is_some() => captured.enter(Section::Output),
+                    _ => {}
+                }
+            }
+            Ok(Event::Empty(e)) if e.name().as_ref() == b"test" => {
+                if let Some((result, definition)) = parse_test(&e, &assembly, report.results.len()) {
+                    report.push(result, definition);
+                }
+            }
+            Ok(Event::Text(e)) => captured.push_text(&e.unescape().unwrap_or_default(), options),
+            Ok(Event::CData(e)) => captured.push_text(&String::from_utf8_lossy(&e), options),
+            Ok(Event::End(e)) => {
+                match e.name().as_ref() {
+                    b"test" => {
+                        if let Some((mut result, definition)) = current.take() {
+                            captured.finish(&mut result);
+                            report.push(result, definition);
+                        }
+                        captured = CapturedText::default();
+                    }
+                    b"failure" => in_failure = false,
+                    b"message" | b"stack-trace" | b"reason" | b"output" => captured.leave(),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(TestamentError::ResultParse(format!(
+                    "xUnit XML parse error: {}",
+                    e
+                )));
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(report)
+}
+
+fn attribute(e: &BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.as_ref() == key)
+        .map(|a| attr_value(&a))
+}
+
+/// Build a result and its definition from a `<test>` element.
+/// `name` is the fully-qualified display name, `type`/`method` the declaring class and method.
+fn parse_test(e: &BytesStart, assembly: &str, index: usize) -> Option<(TestResult, TestDefinition)> {
+    let mut name = String::new();
+    let mut class_name = String::new();
+    let mut method_name = String::new();
+    let mut outcome = TestOutcome::Passed;
+    let mut duration_ms = 0u64;
+
+    for attr in e.attributes().flatten() {
+        match attr.key.as_ref() {
+            b"name" => name = attr_value(&attr),
+            b"type" => class_name = attr_value(&attr),
+            b"method" => method_name = attr_value(&attr),
+            b"result" => {
+                outcome = match attr_value(&attr).as_str() {
+                    "Pass" => TestOutcome::Passed,
+                    "Fail" => TestOutcome::Failed,
+                    _ => TestOutcome::Skipped,
+                };
+            }
+            b"time" => duration_ms = parse_seconds(&attr_value(&attr)),
+            _ => {}
+        }
+    }
+
+    if name.is_empty() {
+        return None;
+    }
+
+    let id = index.to_string();
+    Some((
+        TestResult {
+            test_name: name,
+            outcome,
+            duration_ms,
+            error_message: None,
+            output: None,
+            test_id: Some(id.clone()),
+        },
+        TestDefinition {
+            id,
+            class_name,
+            method_name,
+            storage: assembly.to_string(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <assemblies>
+          <assembly name="/src/Calc.Tests/bin/Debug/net8.0/Calc.Tests.dll" total="3" passed="1" failed="1" skipped="1">
+            <collection name="Test collection for Calc.Tests.MathTests">
+              <test name="Calc.Tests.MathTests.Add(a: 1, b: 2)" type="Calc.Tests.MathTests" method="Add" time="0.0123" result="Pass">
+                <output><![CDATA[adding]]></output>
+              </test>
+              <test name="Calc.Tests.MathTests.Divide" type="Calc.Tests.MathTests" method="Divide" time="1.5" result="Fail">
+                <failure exception-type="System.DivideByZeroException">
+                  <message><![CDATA[Attempted to divide by zero.]]></message>
+                  <stack-trace><![CDATA[at Calc.Tests.MathTests.Divide()]]></stack-trace>
+                </failure>
+              </test>
+              <test name="Calc.Tests.MathTests.Later" type="Calc.Tests.MathTests" method="Later" time="0" result="Skip">
+                <reason><![CDATA[Not implemented]]></reason>
+              </test>
+            </collection>
+          </assembly>
+        </assemblies>"#;
+
+    #[test]
+    fn test_parse_xunit_outcomes_and_durations() {
+        let report = parse_xunit_report(SAMPLE.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(report.results.len(), 3);
+
+        assert_eq!(report.results[0].test_name, "Calc.Tests.MathTests.Add(a: 1, b: 2)");
+        assert_eq!(report.results[0].outcome, TestOutcome::Passed);
+        assert_eq!(report.results[0].duration_ms, 12);
+        assert_eq!(report.results[0].output.as_deref(), Some("adding"));
+
+        assert_eq!(report.results[1].outcome, TestOutcome::Failed);
+        assert_eq!(report.results[1].duration_ms, 1500);
+        assert_eq!(
+            report.results[1].error_message.as_deref(),
+            Some("Attempted to divide by zero.\n\nat Calc.Tests.MathTests.Divide()")
+        );
+
+        assert_eq!(report.results[2].outcome, TestOutcome::Skipped);
+        assert_eq!(report.results[2].error_message.as_deref(), Some("Not implemented"));
+    }
+
+    #[test]
+    fn test_parse_xunit_definitions() {
+        let report = parse_xunit_report(SAMPLE.as_bytes(), &ParseOptions::default()).unwrap();
+        let definition = &report.definitions[report.results[0].test_id.as_deref().unwrap()];
+        assert_eq!(definition.class_name, "Calc.Tests.MathTests");
+        assert_eq!(definition.method_name, "Add");
+        assert_eq!(definition.storage, "/src/Calc.Tests/bin/Debug/net8.0/Calc.Tests.dll");
+        assert_eq!(
+            report.qualified_name(&report.results[0]).as_deref(),
+            Some("Calc.Tests.MathTests.Add(a: 1, b: 2)")
+        );
+    }
+
+    #[test]
+    fn test_parse_xunit_malformed() {
+        assert!(parse_xunit_report("<assemblies><assembly".as_bytes(), &ParseOptions::default()).is_err());
+    }
+}
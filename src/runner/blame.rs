@@ -0,0 +1,101 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::parser::{parse_blame_sequence, Abort, BlamedTest};
+
+/// Tests blamed for a testhost crash or hang, from the attachments that
+/// `dotnet test --blame-crash` / `--blame-hang` wrote under `results_dir`
+/// (`<guid>/Sequence_<guid>.xml`, plus `*_crashdump.dmp` or `*_hangdump.dmp`).
+pub(super) fn collect_blame(results_dir: &Path) -> Vec<BlamedTest> {
+    let mut sequences = Vec::new();
+    let mut dumps = Vec::new();
+    collect_attachments(results_dir, &mut sequences, &mut dumps);
+    if sequences.is_empty() {
+        return Vec::new();
+    }
+    sequences.sort();
+    dumps.sort();
+
+    let hang_dump = dumps.iter().find(|d| is_hang_dump(d));
+    let (abort, dump) = match hang_dump {
+        Some(dump) => (Abort::Hang, Some(dump)),
+        None => (Abort::Crash, dumps.first()),
+    };
+
+    let mut blamed: Vec<BlamedTest> = Vec::new();
+    for sequence in &sequences {
+        let Ok(file) = File::open(sequence) else { continue };
+        for name in parse_blame_sequence(BufReader::new(file)).unwrap_or_default() {
+            if !blamed.iter().any(|b| b.name == name) {
+                blamed.push(BlamedTest { name, abort, dump: dump.cloned() });
+            }
+        }
+    }
+    blamed
+}
+
+fn collect_attachments(dir: &Path, sequences: &mut Vec<PathBuf>, dumps: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Ok(ft) = entry.file_type() else { continue };
+        if ft.is_dir() {
+            collect_attachments(&path, sequences, dumps);
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        if name.starts_with("sequence_") && name.ends_with(".xml") {
+            sequences.push(path);
+        } else if name.ends_with(".dmp") {
+            dumps.push(path);
+        }
+    }
+}
+
+fn is_hang_dump(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n.to_string_lossy().to_lowercase().contains("hangdump"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SEQUENCE: &str = r#"<TestSequence>
+  <Test Name="Ns.Tests.Fast" Source="Tests.dll" Completed="True" />
+  <Test Name="Ns.Tests.Slow" Source="Tests.dll" Completed="False" />
+</TestSequence>"#;
+
+    #[test]
+    fn test_collect_blame_hang_with_dump() {
+        let temp_dir = TempDir::new().unwrap();
+        let attachments = temp_dir.path().join("3f2a");
+        std::fs::create_dir(&attachments).unwrap();
+        std::fs::write(attachments.join("Sequence_3f2a.xml"), SEQUENCE).unwrap();
+        let dump = attachments.join("testhost_4242_20260101T120000_hangdump.dmp");
+        std::fs::write(&dump, "").unwrap();
+
+        assert_eq!(
+            collect_blame(temp_dir.path()),
+            [BlamedTest { name: "Ns.Tests.Slow".to_string(), abort: Abort::Hang, dump: Some(dump) }]
+        );
+    }
+
+    #[test]
+    fn test_collect_blame_crash_without_dump() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("Sequence_1.xml"), SEQUENCE).unwrap();
+
+        let blamed = collect_blame(temp_dir.path());
+        assert_eq!(blamed.len(), 1);
+        assert_eq!((blamed[0].abort, blamed[0].dump.as_ref()), (Abort::Crash, None));
+    }
+
+    #[test]
+    fn test_collect_blame_nothing_written() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(collect_blame(temp_dir.path()).is_empty());
+        assert!(collect_blame(&temp_dir.path().join("missing")).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::parser::{parse_cobertura, CoverageReport};
+
+/// Coverage that coverlet's `XPlat Code Coverage` collector wrote under
+/// `results_dir` (`<guid>/coverage.cobertura.xml`), merged into one report.
+/// None when no coverage file was written.
+pub(super) fn collect_coverage(results_dir: &Path) -> Option<CoverageReport> {
+    let mut files = Vec::new();
+    find_coverage_files(results_dir, &mut files);
+    files.sort();
+
+    let mut coverage: Option<CoverageReport> = None;
+    for path in files {
+        let Ok(file) = File::open(&path) else { continue };
+        if let Ok(report) = parse_cobertura(BufReader::new(file)) {
+            coverage.get_or_insert_with(CoverageReport::default).merge(report);
+        }
+    }
+    coverage
+}
+
+fn find_coverage_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Ok(ft) = entry.file_type() else { continue };
+        if ft.is_dir() {
+            find_coverage_files(&path, files);
+        } else if entry.file_name().to_string_lossy().eq_ignore_ascii_case("coverage.cobertura.xml") {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const COBERTURA: &str = r#"<coverage><packages><package name="Api"><classes>
+  <class name="Api.UserService" filename="/src/Api/UserService.cs"><lines>
+    <line number="3" hits="1" branch="False" />
+    <line number="4" hits="0" branch="False" />
+  </lines></class>
+</classes></package></packages></coverage>"#;
+
+    #[test]
+    fn test_collect_coverage_merges_attachments() {
+        let temp_dir = TempDir::new().unwrap();
+        for guid in ["a1", "b2"] {
+            let attachments = temp_dir.path().join(guid);
+            std::fs::create_dir(&attachments).unwrap();
+            std::fs::write(attachments.join("coverage.cobertura.xml"), COBERTURA).unwrap();
+        }
+
+        let coverage = collect_coverage(temp_dir.path()).unwrap();
+        assert_eq!(coverage.classes.len(), 1);
+        assert_eq!(coverage.classes[0].lines[&3].hits, 2);
+        assert_eq!(coverage.totals().line_percent(), Some(50.0));
+    }
+
+    #[test]
+    fn test_collect_coverage_nothing_written() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(collect_coverage(temp_dir.path()).is_none());
+    }
+}
@@ -2,19 +2,22 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::mpsc;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 
 use crate::error::{Result, TestamentError};
 use crate::model::{Test, TestClass, TestProject};
 use crate::parser::build_test_name_map;
 
+use super::dotnet::dotnet_command;
+use super::executor::split_run_settings;
+
 /// Strip Windows UNC prefix (\\?\) from path - dotnet CLI doesn't handle it well
 fn strip_unc_prefix(path: &Path) -> PathBuf {
     let s = path.to_string_lossy();
-    if s.starts_with(r"\\?\") {
-        PathBuf::from(&s[4..])
-    } else {
-        path.to_path_buf()
+    match s.strip_prefix(r"\\?\") {
+        Some(stripped) => PathBuf::from(stripped),
+        None => path.to_path_buf(),
     }
 }
 
@@ -175,19 +178,29 @@ fn is_test_project_name(name: &str) -> bool {
 /// then discovers tests in background and sends results via channel.
 ///
 /// This allows the TUI to start instantly while test discovery happens in background.
-pub fn discover_projects_lazy(path: &Path) -> Result<(Vec<TestProject>, mpsc::Receiver<DiscoveryEvent>)> {
+/// Tests are listed from the `configuration` build output (the default one when None),
+/// passing `extra_args` to `dotnet test --list-tests`.
+pub fn discover_projects_lazy(
+    path: &Path,
+    configuration: Option<&str>,
+    extra_args: &[String],
+) -> Result<(Vec<TestProject>, mpsc::Receiver<DiscoveryEvent>)> {
     let project_paths = if path.extension().is_some_and(|ext| ext == "csproj") {
         vec![path.to_path_buf()]
     } else {
         parse_solution(path)?
     };
 
-    discover_projects_from_paths(project_paths)
+    discover_projects_from_paths(project_paths, configuration, extra_args)
 }
 
 /// Discover test projects from explicit csproj paths.
 /// Used by PR mode to only load projects containing changed tests.
-pub fn discover_projects_from_paths(project_paths: Vec<PathBuf>) -> Result<(Vec<TestProject>, mpsc::Receiver<DiscoveryEvent>)> {
+pub fn discover_projects_from_paths(
+    project_paths: Vec<PathBuf>,
+    configuration: Option<&str>,
+    extra_args: &[String],
+) -> Result<(Vec<TestProject>, mpsc::Receiver<DiscoveryEvent>)> {
 
     // Create projects without tests (instant)
     let projects: Vec<TestProject> = project_paths
@@ -203,6 +216,8 @@ pub fn discover_projects_from_paths(project_paths: Vec<PathBuf>) -> Result<(Vec<
         .collect();
 
     let (tx, rx) = mpsc::channel();
+    let configuration = configuration.map(str::to_string);
+    let extra_args = extra_args.to_vec();
 
     // Spawn background discovery
     let paths_with_indices: Vec<_> = project_paths.into_iter().enumerate().collect();
@@ -212,10 +227,12 @@ pub fn discover_projects_from_paths(project_paths: Vec<PathBuf>) -> Result<(Vec<
             .into_iter()
             .map(|(idx, path)| {
                 let tx = tx.clone();
+                let configuration = configuration.clone();
+                let extra_args = extra_args.clone();
                 std::thread::spawn(move || {
                     let project_dir = path.parent().unwrap_or(Path::new("."));
 
-                    let test_result = list_tests(&path);
+                    let test_result = list_tests(&path, configuration.as_deref(), &extra_args);
 
                     match test_result {
                         Ok(test_names) => {
@@ -246,22 +263,34 @@ pub fn discover_projects_from_paths(project_paths: Vec<PathBuf>) -> Result<(Vec<
     Ok((projects, rx))
 }
 
+/// The `dotnet test --list-tests --no-build` command for a project.
+fn list_tests_command(project_path: &Path, configuration: Option<&str>, extra_args: &[String]) -> Command {
+    let project_dir = project_path.parent().unwrap_or(Path::new("."));
+    let (extra_options, run_settings) = split_run_settings(extra_args);
+    let mut cmd = dotnet_command();
+    cmd.args(["test", "--list-tests", "--no-build"]);
+    if let Some(configuration) = configuration {
+        cmd.args(["--configuration", configuration]);
+    }
+    cmd.args(extra_options);
+    cmd.arg(project_path);
+    // Inline RunSettings must come after every option
+    cmd.args(run_settings);
+    cmd.current_dir(project_dir);
+    cmd
+}
+
 /// Run `dotnet test --list-tests` to get test names, then attempt to resolve
 /// fully-qualified names via `dotnet vstest /ListFullyQualifiedTests`.
 /// First tries cache, then --no-build for speed.
-fn list_tests(project_path: &Path) -> Result<Vec<String>> {
+fn list_tests(project_path: &Path, configuration: Option<&str>, extra_args: &[String]) -> Result<Vec<String>> {
     // Try cache first
-    if let Some(cached) = load_cache(project_path) {
+    if let Some(cached) = load_cache(project_path, configuration, extra_args) {
         return Ok(cached);
     }
     
-    let project_dir = project_path.parent().unwrap_or(Path::new("."));
-    
     // First try without building (fast if already built)
-    let output = Command::new("dotnet")
-        .args(["test", "--list-tests", "--no-build"])
-        .arg(project_path)
-        .current_dir(project_dir)
+    let output = list_tests_command(project_path, configuration, extra_args)
         .output()
         .map_err(|e| TestamentError::DotnetExecution(format!("Failed to spawn: {}", e)))?;
 
@@ -331,14 +360,14 @@ fn list_tests(project_path: &Path) -> Result<Vec<String>> {
     if let Some(dll) = dll_path {
         if let Some(fqn_tests) = list_tests_fqn(&dll) {
             if fqn_tests.len() == tests.len() {
-                save_cache(project_path, &fqn_tests);
+                save_cache(project_path, configuration, extra_args, &fqn_tests);
                 return Ok(fqn_tests);
             }
         }
     }
 
     // Save to cache for next time
-    save_cache(project_path, &tests);
+    save_cache(project_path, configuration, extra_args, &tests);
     
     Ok(tests)
 }
@@ -350,7 +379,7 @@ fn list_tests_fqn(dll_path: &Path) -> Option<Vec<String>> {
     let mut hasher = DefaultHasher::new();
     dll_path.hash(&mut hasher);
     let temp_file = std::env::temp_dir().join(format!("testament_fqn_{:x}.txt", hasher.finish()));
-    let output = Command::new("dotnet")
+    let output = dotnet_command()
         .arg("vstest")
         .arg(dll_path)
         .arg("/ListFullyQualifiedTests")
@@ -378,75 +407,196 @@ fn list_tests_fqn(dll_path: &Path) -> Option<Vec<String>> {
     Some(tests)
 }
 
-/// Get cache file path for a project
-fn get_cache_path(project_path: &Path) -> PathBuf {
+/// Get cache file path for a project built in `configuration` and listed with
+/// `extra_args` (e.g. `--framework` or `-p:` properties change the tests found)
+fn get_cache_path(project_path: &Path, configuration: Option<&str>, extra_args: &[String]) -> PathBuf {
     let mut hasher = DefaultHasher::new();
     project_path.hash(&mut hasher);
+    configuration.map(str::to_lowercase).hash(&mut hasher);
+    extra_args.hash(&mut hasher);
     let hash = hasher.finish();
     std::env::temp_dir().join(format!("testament_discovery_{:x}.cache", hash))
 }
 
-/// Get the modification time of a project in millis (max of csproj, source files, and DLLs)
-fn get_project_mtime(project_path: &Path) -> Option<u128> {
-    let csproj_mtime = std::fs::metadata(project_path)
-        .and_then(|m| m.modified())
-        .ok()?
-        .duration_since(std::time::UNIX_EPOCH)
-        .ok()?
-        .as_millis();
-
-    let mut max_mtime = csproj_mtime;
+/// Get the modification time of a project in millis (max of csproj, source files, and
+/// the DLLs of `configuration`)
+fn get_project_mtime(project_path: &Path, configuration: Option<&str>) -> Option<u128> {
+    let mut max_mtime = file_mtime(project_path)?;
 
     if let Some(project_dir) = project_path.parent() {
         // Check newest .cs source file - detects renamed/added/removed test methods
-        if let Ok(cs_mtime) = newest_file_mtime(project_dir, "cs") {
-            max_mtime = max_mtime.max(cs_mtime);
-        }
+        max_mtime = max_mtime.max(newest_file_mtime(project_dir, &|p| has_extension(p, "cs")));
 
         // Check newest DLL in bin/ - rebuilds update DLLs even when csproj doesn't change
-        let bin_dir = project_dir.join("bin");
-        if let Ok(dll_mtime) = newest_file_mtime(&bin_dir, "dll") {
-            max_mtime = max_mtime.max(dll_mtime);
-        }
+        let bin_dir = output_dir(project_path, configuration)?;
+        max_mtime = max_mtime.max(newest_file_mtime(&bin_dir, &|p| has_extension(p, "dll")));
     }
 
     Some(max_mtime)
 }
 
-/// Find the newest file with the given extension under a directory (recursive).
-fn newest_file_mtime(dir: &Path, ext: &str) -> std::result::Result<u128, std::io::Error> {
+/// Newest modification time (0 if none) of files under `dir` matching `matches`,
+/// skipping build output (`bin`, `obj`) and hidden directories below it.
+fn newest_file_mtime(dir: &Path, matches: &dyn Fn(&Path) -> bool) -> u128 {
     let mut max: u128 = 0;
-    newest_file_mtime_recursive(dir, ext, &mut max)?;
-    Ok(max)
+    newest_file_mtime_recursive(dir, matches, &mut max);
+    max
 }
 
-fn newest_file_mtime_recursive(dir: &Path, ext: &str, max: &mut u128) -> std::result::Result<(), std::io::Error> {
-    for entry in std::fs::read_dir(dir)? {
-        let entry = entry?;
-        let ft = entry.file_type()?;
+fn newest_file_mtime_recursive(dir: &Path, matches: &dyn Fn(&Path) -> bool, max: &mut u128) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Ok(ft) = entry.file_type() else { continue };
         if ft.is_dir() {
-            let _ = newest_file_mtime_recursive(&entry.path(), ext, max);
-        } else if ft.is_file() && entry.path().extension().is_some_and(|e| e == ext) {
-            if let Ok(mtime) = entry.metadata()
-                .and_then(|m| m.modified())
-                .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis())
-            {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name != "bin" && name != "obj" && !name.starts_with('.') {
+                newest_file_mtime_recursive(&path, matches, max);
+            }
+        } else if ft.is_file() && matches(&path) {
+            if let Some(mtime) = file_mtime(&path) {
                 *max = (*max).max(mtime);
             }
         }
     }
-    Ok(())
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension().is_some_and(|e| e == ext)
+}
+
+fn file_mtime(path: &Path) -> Option<u128> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_millis())
+}
+
+/// Find the .csproj file for a given source file by searching parent directories
+pub fn find_csproj_for_file(file_path: &Path) -> Option<PathBuf> {
+    let mut dir = file_path.parent()?;
+    
+    loop {
+        // Look for .csproj files in this directory
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "csproj") {
+                    return Some(path);
+                }
+            }
+        }
+        
+        // Move to parent directory
+        dir = dir.parent()?;
+    }
+}
+
+/// Indices of the `projects` affected by changes to `changed` files: those owning
+/// a changed file (the nearest `.csproj`) or referencing, directly or not, a
+/// project that does.
+pub fn affected_projects(projects: &[PathBuf], changed: &[PathBuf]) -> Vec<usize> {
+    let owners: HashSet<PathBuf> = changed
+        .iter()
+        .filter_map(|path| match path.extension() {
+            Some(ext) if ext == "csproj" => Some(path.clone()),
+            _ => find_csproj_for_file(path),
+        })
+        .map(|owner| canonical(&owner))
+        .collect();
+    projects
+        .iter()
+        .enumerate()
+        .filter(|(_, project)| {
+            let mut closure = HashSet::new();
+            referenced_projects(project, &mut closure);
+            !closure.is_disjoint(&owners)
+        })
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// A project and, recursively, every project it references.
+fn referenced_projects(project_path: &Path, visited: &mut HashSet<PathBuf>) {
+    if !visited.insert(canonical(project_path)) {
+        return;
+    }
+    if let Some(project_dir) = project_path.parent() {
+        for reference in project_references(project_path) {
+            referenced_projects(&project_dir.join(reference), visited);
+        }
+    }
+}
+
+/// Whether a project's build output is missing or older than its sources: the
+/// `.csproj` and `.cs` files of the project and of the projects it references.
+pub fn is_build_stale(project_path: &Path, configuration: Option<&str>) -> bool {
+    let Some(output_mtime) = output_dll_mtime(project_path, configuration) else {
+        return true;
+    };
+    let mut visited = HashSet::new();
+    sources_mtime(project_path, &mut visited) > output_mtime
+}
+
+/// Build output directory of a project: `bin/<Configuration>`, where no
+/// configuration means the `dotnet build` default, `Debug`.
+fn output_dir(project_path: &Path, configuration: Option<&str>) -> Option<PathBuf> {
+    let bin_dir = project_path.parent()?.join("bin");
+    Some(bin_dir.join(configuration.unwrap_or("Debug")))
+}
+
+/// Newest `<ProjectName>.dll` in the project's build output for `configuration`
+fn output_dll_mtime(project_path: &Path, configuration: Option<&str>) -> Option<u128> {
+    let dll_name = format!("{}.dll", project_path.file_stem()?.to_str()?);
+    let bin_dir = output_dir(project_path, configuration)?;
+    let max = newest_file_mtime(&bin_dir, &|p| p.file_name().is_some_and(|n| n == dll_name.as_str()));
+    (max > 0).then_some(max)
+}
+
+/// Newest source modification time of a project and, recursively, its project references.
+fn sources_mtime(project_path: &Path, visited: &mut HashSet<PathBuf>) -> u128 {
+    if !visited.insert(canonical(project_path)) {
+        return 0;
+    }
+    let mut max = file_mtime(project_path).unwrap_or(0);
+    if let Some(project_dir) = project_path.parent() {
+        max = max.max(newest_file_mtime(project_dir, &|p| has_extension(p, "cs")));
+        for reference in project_references(project_path) {
+            max = max.max(sources_mtime(&project_dir.join(reference), visited));
+        }
+    }
+    max
+}
+
+/// `Include` paths of the `<ProjectReference>` items in a project file
+fn project_references(project_path: &Path) -> Vec<PathBuf> {
+    let Ok(content) = std::fs::read_to_string(project_path) else {
+        return Vec::new();
+    };
+    content
+        .split("<ProjectReference")
+        .skip(1)
+        .filter_map(|item| {
+            let start = item.find("Include=\"")? + "Include=\"".len();
+            let end = item[start..].find('"')?;
+            // Project files written on Windows use backslashes
+            Some(PathBuf::from(item[start..start + end].replace('\\', "/")))
+        })
+        .collect()
 }
 
 /// Try to load cached test list
-fn load_cache(project_path: &Path) -> Option<Vec<String>> {
-    let cache_path = get_cache_path(project_path);
+fn load_cache(project_path: &Path, configuration: Option<&str>, extra_args: &[String]) -> Option<Vec<String>> {
+    let cache_path = get_cache_path(project_path, configuration, extra_args);
     let content = std::fs::read_to_string(&cache_path).ok()?;
     let mut lines = content.lines();
     
     // First line is the mtime
     let cached_mtime: u128 = lines.next()?.parse().ok()?;
-    let current_mtime = get_project_mtime(project_path)?;
+    let current_mtime = get_project_mtime(project_path, configuration)?;
     
     if cached_mtime != current_mtime {
         return None; // Cache is stale
@@ -456,9 +606,9 @@ fn load_cache(project_path: &Path) -> Option<Vec<String>> {
 }
 
 /// Save test list to cache
-fn save_cache(project_path: &Path, tests: &[String]) {
-    let Some(mtime) = get_project_mtime(project_path) else { return };
-    let cache_path = get_cache_path(project_path);
+fn save_cache(project_path: &Path, configuration: Option<&str>, extra_args: &[String], tests: &[String]) {
+    let Some(mtime) = get_project_mtime(project_path, configuration) else { return };
+    let cache_path = get_cache_path(project_path, configuration, extra_args);
     
     let content = std::iter::once(mtime.to_string())
         .chain(tests.iter().cloned())
@@ -607,7 +757,7 @@ fn group_tests_by_fqn(test_names: Vec<String>) -> Vec<TestClass> {
 }
 
 /// Convert a HashMap of class -> tests into sorted Vec<TestClass>.
-fn build_test_classes(classes: std::collections::HashMap<String, Vec<Test>>) -> Vec<TestClass> {
+pub(super) fn build_test_classes(classes: std::collections::HashMap<String, Vec<Test>>) -> Vec<TestClass> {
     let mut result: Vec<TestClass> = classes
         .into_iter()
         .map(|(full_name, mut tests)| {
@@ -636,6 +786,131 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    fn set_mtime(path: &Path, secs: u64) {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        fs::File::options().write(true).open(path).unwrap().set_modified(time).unwrap();
+    }
+
+    #[test]
+    fn test_is_build_stale() {
+        let temp_dir = TempDir::new().unwrap();
+        let app_dir = temp_dir.path().join("App");
+        let tests_dir = temp_dir.path().join("App.Tests");
+        let output_dir = tests_dir.join("bin").join("Debug").join("net8.0");
+        fs::create_dir_all(&app_dir).unwrap();
+        fs::create_dir_all(&output_dir).unwrap();
+
+        let app_csproj = app_dir.join("App.csproj");
+        let app_source = app_dir.join("Calc.cs");
+        let tests_csproj = tests_dir.join("App.Tests.csproj");
+        let test_source = tests_dir.join("CalcTests.cs");
+        let dll = output_dir.join("App.Tests.dll");
+        fs::write(&app_csproj, "<Project />").unwrap();
+        fs::write(&app_source, "").unwrap();
+        fs::write(
+            &tests_csproj,
+            r#"<Project><ItemGroup><ProjectReference Include="..\App\App.csproj" /></ItemGroup></Project>"#,
+        )
+        .unwrap();
+        fs::write(&test_source, "").unwrap();
+
+        // Never built
+        assert!(is_build_stale(&tests_csproj, None));
+
+        fs::write(&dll, "").unwrap();
+        for path in [&app_csproj, &app_source, &tests_csproj, &test_source] {
+            set_mtime(path, 1_000);
+        }
+        set_mtime(&dll, 2_000);
+        assert!(!is_build_stale(&tests_csproj, None));
+        assert!(!is_build_stale(&tests_csproj, Some("Debug")));
+        // Not built in Release
+        assert!(is_build_stale(&tests_csproj, Some("Release")));
+
+        // A change in a referenced project makes the tests stale too
+        set_mtime(&app_source, 3_000);
+        assert!(is_build_stale(&tests_csproj, None));
+
+        // A newer Release build doesn't count when no configuration is set (Debug)
+        let release_dir = tests_dir.join("bin").join("Release").join("net8.0");
+        fs::create_dir_all(&release_dir).unwrap();
+        let release_dll = release_dir.join("App.Tests.dll");
+        fs::write(&release_dll, "").unwrap();
+        set_mtime(&release_dll, 4_000);
+        assert!(is_build_stale(&tests_csproj, None));
+        assert!(!is_build_stale(&tests_csproj, Some("Release")));
+    }
+
+    #[test]
+    fn test_list_tests_command_puts_run_settings_last() {
+        let project = Path::new("/src/App.Tests/App.Tests.csproj");
+        let extra_args = ["--no-restore".to_string(), "--".to_string(), "RunConfiguration.X=1".to_string()];
+        let cmd = list_tests_command(project, Some("Debug"), &extra_args);
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(
+            args,
+            [
+                "test", "--list-tests", "--no-build", "--configuration", "Debug", "--no-restore",
+                "/src/App.Tests/App.Tests.csproj", "--", "RunConfiguration.X=1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cache_path_depends_on_extra_args() {
+        let project = Path::new("/src/App.Tests/App.Tests.csproj");
+        let net8 = ["--framework".to_string(), "net8.0".to_string()];
+        let net9 = ["--framework".to_string(), "net9.0".to_string()];
+        assert_eq!(get_cache_path(project, Some("Debug"), &net8), get_cache_path(project, Some("debug"), &net8));
+        assert_ne!(get_cache_path(project, Some("Debug"), &net8), get_cache_path(project, Some("Debug"), &net9));
+        assert_ne!(get_cache_path(project, Some("Debug"), &net8), get_cache_path(project, Some("Debug"), &[]));
+    }
+
+    #[test]
+    fn test_affected_projects() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for (dir, csproj) in [
+            ("Core", "<Project />".to_string()),
+            ("App", r#"<Project><ItemGroup><ProjectReference Include="..\Core\Core.csproj" /></ItemGroup></Project>"#.to_string()),
+            ("App.Tests", r#"<Project><ItemGroup><ProjectReference Include="..\App\App.csproj" /></ItemGroup></Project>"#.to_string()),
+            ("Other.Tests", "<Project />".to_string()),
+        ] {
+            fs::create_dir_all(root.join(dir).join("Models")).unwrap();
+            fs::write(root.join(dir).join(format!("{}.csproj", dir)), csproj).unwrap();
+        }
+        let projects = vec![root.join("App.Tests/App.Tests.csproj"), root.join("Other.Tests/Other.Tests.csproj")];
+
+        // Referenced transitively, from a nested directory
+        assert_eq!(affected_projects(&projects, &[root.join("Core/Models/User.cs")]), vec![0]);
+        assert_eq!(affected_projects(&projects, &[root.join("Other.Tests/UserTests.cs")]), vec![1]);
+        assert_eq!(affected_projects(&projects, &[root.join("App.Tests/App.Tests.csproj"), root.join("Other.Tests/A.cs")]), vec![0, 1]);
+        assert!(affected_projects(&projects, &[root.join("README.cs")]).is_empty());
+    }
+
+    #[test]
+    fn test_project_references() {
+        let temp_dir = TempDir::new().unwrap();
+        let csproj = temp_dir.path().join("A.Tests.csproj");
+        fs::write(
+            &csproj,
+            r#"<Project>
+                <ItemGroup>
+                    <ProjectReference Include="..\A\A.csproj" />
+                    <ProjectReference Include="../B/B.csproj">
+                        <Private>false</Private>
+                    </ProjectReference>
+                    <PackageReference Include="xunit" Version="2.9.0" />
+                </ItemGroup>
+            </Project>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            project_references(&csproj),
+            [PathBuf::from("../A/A.csproj"), PathBuf::from("../B/B.csproj")]
+        );
+    }
+
     // is_test_project_name tests
     #[test]
     fn test_is_test_project_name_with_tests_suffix() {
@@ -1008,7 +1283,7 @@ mod tests {
         fs::write(temp_dir.path().join("Second.sln"), "").unwrap();
 
         let result = find_solution(temp_dir.path()).unwrap();
-        assert!(result.extension().map_or(false, |ext| ext == "sln"));
+        assert!(result.extension().is_some_and(|ext| ext == "sln"));
     }
 
     // parse_solution tests
@@ -0,0 +1,197 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// The dotnet executable every build, run and discovery uses.
+static DOTNET_HOST: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `host` instead of `dotnet` from PATH. Only the first call has an effect.
+pub fn set_dotnet_host(host: PathBuf) {
+    let _ = DOTNET_HOST.set(host);
+}
+
+pub fn dotnet_host() -> &'static Path {
+    DOTNET_HOST.get().map(PathBuf::as_path).unwrap_or(Path::new("dotnet"))
+}
+
+/// A `dotnet` command using the configured host.
+pub fn dotnet_command() -> Command {
+    Command::new(dotnet_host())
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct GlobalJson {
+    sdk: Option<SdkPin>,
+}
+
+/// The `sdk` section of `global.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SdkPin {
+    pub version: String,
+    #[serde(default = "default_roll_forward")]
+    pub roll_forward: String,
+}
+
+fn default_roll_forward() -> String {
+    "latestPatch".to_string()
+}
+
+/// The SDK pinned by the nearest `global.json` and whether it is installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdkStatus {
+    pub pin: SdkPin,
+    /// None when the installed SDKs couldn't be listed
+    pub installed: Option<Vec<String>>,
+}
+
+impl SdkStatus {
+    /// Installed SDK that `dotnet` would pick for the pin, if any.
+    pub fn resolved(&self) -> Option<&str> {
+        self.installed
+            .as_ref()?
+            .iter()
+            .filter(|version| sdk_satisfies(&self.pin, version))
+            .max_by_key(|version| parse_sdk_version(version))
+            .map(String::as_str)
+    }
+
+    /// For the header, e.g. "SDK 8.0.100".
+    pub fn summary(&self) -> String {
+        match self.resolved() {
+            Some(version) if version != self.pin.version => format!("SDK {} ({})", self.pin.version, version),
+            _ => format!("SDK {}", self.pin.version),
+        }
+    }
+
+    /// Explains why builds will fail when no installed SDK satisfies the pin.
+    pub fn warning(&self) -> Option<String> {
+        let installed = self.installed.as_ref()?;
+        if self.resolved().is_some() {
+            return None;
+        }
+        let available = if installed.is_empty() { "none".to_string() } else { installed.join(", ") };
+        Some(format!(
+            "global.json requires SDK {} (rollForward {}), which is not installed. Installed: {}",
+            self.pin.version, self.pin.roll_forward, available
+        ))
+    }
+}
+
+/// Read the `global.json` in `dir` or the nearest parent directory and check its
+/// SDK against `dotnet --list-sdks`. None without a pinned SDK.
+pub fn sdk_status(dir: &Path) -> Result<Option<SdkStatus>, String> {
+    let Some(path) = dir.ancestors().map(|d| d.join("global.json")).find(|p| p.is_file()) else {
+        return Ok(None);
+    };
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let Some(pin) = parse_global_json(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))? else {
+        return Ok(None);
+    };
+    Ok(Some(SdkStatus { pin, installed: installed_sdks() }))
+}
+
+fn parse_global_json(content: &str) -> Result<Option<SdkPin>, serde_json::Error> {
+    let global: GlobalJson = serde_json::from_str(content)?;
+    Ok(global.sdk)
+}
+
+fn installed_sdks() -> Option<Vec<String>> {
+    let output = dotnet_command().arg("--list-sdks").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_list_sdks(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Versions from `dotnet --list-sdks` lines like `8.0.100 [/usr/share/dotnet/sdk]`.
+fn parse_list_sdks(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// (major, minor, feature band, patch, is release) of e.g. `8.0.203` or `9.0.100-rc.1.24452.12`.
+fn parse_sdk_version(version: &str) -> Option<(u32, u32, u32, u32, bool)> {
+    let (numbers, prerelease) = match version.split_once('-') {
+        Some((numbers, _)) => (numbers, true),
+        None => (version, false),
+    };
+    let mut parts = numbers.split('.').map(|p| p.parse::<u32>().ok());
+    let (major, minor, patch) = (parts.next()??, parts.next()??, parts.next()??);
+    Some((major, minor, patch / 100, patch % 100, !prerelease))
+}
+
+/// Whether an installed SDK can be used for `pin` under its rollForward policy.
+fn sdk_satisfies(pin: &SdkPin, installed: &str) -> bool {
+    let (Some(want), Some(have)) = (parse_sdk_version(&pin.version), parse_sdk_version(installed)) else {
+        return false;
+    };
+    if have < want {
+        return false;
+    }
+    match pin.roll_forward.as_str() {
+        "disable" => installed == pin.version,
+        "patch" | "latestPatch" => have.0 == want.0 && have.1 == want.1 && have.2 == want.2,
+        "feature" | "latestFeature" => have.0 == want.0 && have.1 == want.1,
+        "minor" | "latestMinor" => have.0 == want.0,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(version: &str, roll_forward: &str) -> SdkPin {
+        SdkPin { version: version.to_string(), roll_forward: roll_forward.to_string() }
+    }
+
+    #[test]
+    fn test_parse_global_json() {
+        let json = r#"{ "sdk": { "version": "8.0.100", "rollForward": "latestFeature" } }"#;
+        assert_eq!(parse_global_json(json).unwrap(), Some(pin("8.0.100", "latestFeature")));
+
+        let json = r#"{ "sdk": { "version": "8.0.100" }, "msbuild-sdks": {} }"#;
+        assert_eq!(parse_global_json(json).unwrap(), Some(pin("8.0.100", "latestPatch")));
+
+        assert_eq!(parse_global_json(r#"{ "msbuild-sdks": {} }"#).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_list_sdks() {
+        let output = "6.0.428 [/usr/share/dotnet/sdk]\n8.0.404 [/usr/share/dotnet/sdk]\n";
+        assert_eq!(parse_list_sdks(output), vec!["6.0.428", "8.0.404"]);
+    }
+
+    #[test]
+    fn test_sdk_satisfies_roll_forward() {
+        assert!(sdk_satisfies(&pin("8.0.100", "latestPatch"), "8.0.110"));
+        assert!(!sdk_satisfies(&pin("8.0.100", "latestPatch"), "8.0.200"));
+        assert!(!sdk_satisfies(&pin("8.0.110", "latestPatch"), "8.0.100"));
+        assert!(sdk_satisfies(&pin("8.0.100", "latestFeature"), "8.0.404"));
+        assert!(!sdk_satisfies(&pin("8.0.100", "latestFeature"), "9.0.100"));
+        assert!(sdk_satisfies(&pin("8.0.100", "latestMajor"), "9.0.100"));
+        assert!(!sdk_satisfies(&pin("8.0.100", "disable"), "8.0.101"));
+        assert!(!sdk_satisfies(&pin("9.0.100", "latestPatch"), "9.0.100-rc.2.24474.11"));
+    }
+
+    #[test]
+    fn test_sdk_status_warning() {
+        let status = SdkStatus {
+            pin: pin("8.0.100", "latestPatch"),
+            installed: Some(vec!["8.0.104".to_string(), "9.0.100".to_string()]),
+        };
+        assert_eq!(status.resolved(), Some("8.0.104"));
+        assert_eq!(status.summary(), "SDK 8.0.100 (8.0.104)");
+        assert_eq!(status.warning(), None);
+
+        let status = SdkStatus { pin: pin("7.0.100", "latestPatch"), ..status };
+        assert_eq!(status.resolved(), None);
+        assert!(status.warning().unwrap().contains("Installed: 8.0.104, 9.0.100"));
+    }
+}
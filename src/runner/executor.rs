@@ -1,179 +1,748 @@
+use std::collections::BTreeMap;
+use std::fs::File;
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::{ChildStderr, Command, Stdio};
 use std::io::{BufRead, BufReader};
-use std::sync::mpsc::{self, Receiver};
-use std::thread;
+use std::sync::mpsc::{self, Receiver, RecvError, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
-use crate::parser::{parse_trx, TestResult};
+use crate::config::{BuildMode, Config, RunProfile, TimeLimit};
+use crate::model::{OutputLine, OutputSource};
+use crate::parser::{
+    blame_report, parse_debug_process_id, parse_diagnostic, parse_report, parse_result_line, BlamedTest, CoverageReport, ParseOptions,
+    ResultFormat, TestReport, TestResult,
+};
+
+use super::blame::collect_blame;
+use super::coverage::collect_coverage;
+use super::discovery::is_build_stale;
+use super::dotnet::dotnet_command;
+use super::filter::tests_filters;
+use super::process::{new_process_group, CancelToken};
 
 pub enum ExecutorEvent {
-    OutputLine(String),
+    /// Output of `dotnet test` that isn't a test result
+    OutputLine(OutputLine),
     BuildCompleted(bool),
-    Completed(Vec<TestResult>),
+    /// A test run is building the project first (its binaries were stale)
+    BuildStarted,
+    /// A line of `dotnet build` output, as it is printed
+    BuildOutput(OutputLine),
+    /// A test finished, as reported on the console while the run continues.
+    /// The final `Completed` report is authoritative.
+    TestFinished(TestResult),
+    /// testhost started with `VSTEST_HOST_DEBUG` and waits for a debugger to attach to this process id
+    DebuggerWaiting(u32),
+    /// Code coverage of the run, sent just before `Completed` when collected
+    Coverage(CoverageReport),
+    Completed(TestReport),
     Error(String),
+    /// The build or run was cancelled and its processes killed
+    Cancelled,
+}
+
+/// A build or test run in progress: its events, and a way to stop it.
+pub struct ExecutionHandle {
+    events: Receiver<ExecutorEvent>,
+    cancel: CancelToken,
+}
+
+impl ExecutionHandle {
+    pub fn try_recv(&self) -> Result<ExecutorEvent, TryRecvError> {
+        self.events.try_recv()
+    }
+
+    /// Wait for the next event. Fails once the run is over and every event was received.
+    pub fn recv(&self) -> Result<ExecutorEvent, RecvError> {
+        self.events.recv()
+    }
+
+    /// Kill the `dotnet` process tree. A `Cancelled` event follows once it has exited.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
 }
 
 pub struct TestExecutor {
     project_path: std::path::PathBuf,
+    result_format: ResultFormat,
+    build_mode: BuildMode,
+    timeout: Option<TimeLimit>,
+    collectors: Collectors,
+    configuration: Option<String>,
+    extra_args: Vec<String>,
+    profile: RunProfile,
+    debug: bool,
+}
+
+/// Data collectors requested from `dotnet test`, which write attachments to
+/// the results directory.
+#[derive(Debug, Clone, Copy, Default)]
+struct Collectors {
+    blame: Blame,
+    coverage: bool,
+}
+
+impl Collectors {
+    fn enabled(&self) -> bool {
+        self.blame.enabled() || self.coverage
+    }
+
+    /// The collectors of one run. A debug run has no hang timeout, like it has no
+    /// run timeout: it waits for the debugger to attach.
+    fn for_run(self, debug: bool) -> Self {
+        if debug {
+            Self { blame: Blame { hang_timeout: None, ..self.blame }, ..self }
+        } else {
+            self
+        }
+    }
+
+    /// `dotnet test` arguments requesting the collectors.
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.blame.crash {
+            args.push("--blame-crash".to_string());
+        }
+        if let Some(limit) = self.blame.hang_timeout {
+            args.extend(["--blame-hang".to_string(), "--blame-hang-timeout".to_string(), limit.to_string()]);
+        }
+        if self.coverage {
+            args.extend(["--collect".to_string(), "XPlat Code Coverage".to_string()]);
+        }
+        args
+    }
+}
+
+/// Blame data collection requested from `dotnet test`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Blame {
+    crash: bool,
+    hang_timeout: Option<TimeLimit>,
+}
+
+impl Blame {
+    fn enabled(&self) -> bool {
+        self.crash || self.hang_timeout.is_some()
+    }
 }
 
 impl TestExecutor {
     pub fn new(project_path: &Path) -> Self {
         Self {
             project_path: project_path.to_path_buf(),
+            result_format: ResultFormat::default(),
+            build_mode: BuildMode::Never,
+            timeout: None,
+            collectors: Collectors::default(),
+            configuration: None,
+            extra_args: Vec::new(),
+            profile: RunProfile::default(),
+            debug: false,
         }
     }
 
-    pub fn build(&self) -> Receiver<ExecutorEvent> {
+    /// Executor set up from the `[runner]` settings of `.testament.toml`, with the
+    /// active run profile.
+    pub fn configured(project_path: &Path, config: &Config) -> Self {
+        let runner = &config.runner;
+        Self::new(project_path)
+            .with_result_format(runner.logger)
+            .with_build_mode(runner.build)
+            .with_timeout(runner.timeout)
+            .with_blame(runner.blame_crash, runner.blame_hang_timeout)
+            .with_coverage(runner.coverage)
+            .with_profile(config.active_profile().map(|(_, profile)| profile))
+            .with_configuration(config.configuration())
+            .with_extra_args(&runner.extra_args)
+    }
+
+    /// Ask `dotnet test` for results in `format` instead of TRX.
+    pub fn with_result_format(mut self, format: ResultFormat) -> Self {
+        self.result_format = format;
+        self
+    }
+
+    /// Build the project before running its tests, always or only when stale.
+    pub fn with_build_mode(mut self, mode: BuildMode) -> Self {
+        self.build_mode = mode;
+        self
+    }
+
+    /// Kill test runs (not the build before them) that take longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Option<TimeLimit>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run with `--blame-crash` and/or `--blame-hang`, so the test that was running
+    /// when testhost crashed or hung is reported as failed, with its dump.
+    pub fn with_blame(mut self, crash: bool, hang_timeout: Option<TimeLimit>) -> Self {
+        self.collectors.blame = Blame { crash, hang_timeout };
+        self
+    }
+
+    /// Collect code coverage with `--collect "XPlat Code Coverage"` (coverlet),
+    /// sent as a `Coverage` event before `Completed`.
+    pub fn with_coverage(mut self, coverage: bool) -> Self {
+        self.collectors.coverage = coverage;
+        self
+    }
+
+    /// Build and run with a profile's environment, runsettings, arguments and loggers.
+    /// Its configuration is applied through `with_configuration`.
+    pub fn with_profile(mut self, profile: Option<&RunProfile>) -> Self {
+        self.profile = profile.cloned().unwrap_or_default();
+        self
+    }
+
+    /// Pass `args` to every `dotnet build` and `dotnet test`.
+    pub fn with_extra_args(mut self, args: &[String]) -> Self {
+        self.extra_args = args.to_vec();
+        self
+    }
+
+    /// Build and run with `--configuration`, e.g. `Release`.
+    pub fn with_configuration(mut self, configuration: Option<&str>) -> Self {
+        self.configuration = configuration.map(str::to_string);
+        self
+    }
+
+    /// Run with `VSTEST_HOST_DEBUG=1`: testhost waits for a debugger before running
+    /// the tests, and `DebuggerWaiting` tells which process to attach to. The run
+    /// timeout doesn't apply.
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Incremental `dotnet build`, streaming its output as `BuildOutput`.
+    pub fn build(&self) -> ExecutionHandle {
         let (tx, rx) = mpsc::channel();
         let project_path = self.project_path.clone();
+        let configuration = self.configuration.clone();
+        let args = [split_run_settings(&self.extra_args).0, &self.profile.args].concat();
+        let env = self.profile.env.clone();
+        let cancel = CancelToken::default();
+        let token = cancel.clone();
 
         thread::spawn(move || {
-            let project_dir = project_path.parent().unwrap_or(Path::new("."));
-            let output = match Command::new("dotnet")
-                .args(["build", "--verbosity", "minimal"])
-                .arg(&project_path)
-                .current_dir(project_dir)
-                .output()
-            {
-                Ok(output) => output,
-                Err(e) => {
-                    let _ = tx.send(ExecutorEvent::Error(format!("Failed to start dotnet: {}", e)));
-                    let _ = tx.send(ExecutorEvent::BuildCompleted(false));
-                    return;
+            let event = match run_build(&project_path, configuration.as_deref(), &args, &env, &tx, &token) {
+                BuildOutcome::Succeeded => ExecutorEvent::BuildCompleted(true),
+                BuildOutcome::Failed => ExecutorEvent::BuildCompleted(false),
+                BuildOutcome::Cancelled => ExecutorEvent::Cancelled,
+                BuildOutcome::NotStarted(msg) => {
+                    let _ = tx.send(ExecutorEvent::Error(msg));
+                    ExecutorEvent::BuildCompleted(false)
                 }
             };
-
-            let success = output.status.success();
-            if !success {
-                // Show build errors
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                for line in stdout.lines().chain(stderr.lines()) {
-                    if !line.trim().is_empty() {
-                        let _ = tx.send(ExecutorEvent::OutputLine(line.to_string()));
-                    }
-                }
-            }
-
-            let _ = tx.send(ExecutorEvent::BuildCompleted(success));
+            let _ = tx.send(event);
         });
 
-        rx
+        ExecutionHandle { events: rx, cancel }
     }
 
-    pub fn run(&self, test_filter: Option<Vec<String>>) -> Receiver<ExecutorEvent> {
+    /// Run tests, all of them or only `test_filter`. Selections too long for one
+    /// `--filter` run as several `dotnet test` invocations, one after another,
+    /// whose results are merged into a single `Completed` report.
+    pub fn run(&self, test_filter: Option<Vec<String>>) -> ExecutionHandle {
         let (tx, rx) = mpsc::channel();
         let project_path = self.project_path.clone();
+        let options = BatchOptions {
+            format: self.result_format,
+            collectors: self.collectors,
+            configuration: self.configuration.clone(),
+            extra_args: self.extra_args.clone(),
+            profile: self.profile.clone(),
+            debug: self.debug,
+        };
+        let build_mode = self.build_mode;
+        // Nobody wants the run killed while stepping through a test
+        let timeout = self.timeout.filter(|_| !self.debug);
+        let cancel = CancelToken::default();
+        let token = cancel.clone();
 
         thread::spawn(move || {
-            let project_dir = project_path.parent().unwrap_or(Path::new("."));
-            // Unique TRX path per run to avoid stale results from crashes
-            let trx_path = std::env::temp_dir().join(format!(
-                "testament_{}_{}.trx",
-                std::process::id(),
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_nanos()
-            ));
-            // Remove any stale file
-            let _ = std::fs::remove_file(&trx_path);
-
-            let mut cmd = Command::new("dotnet");
-            cmd.args([
-                "test",
-                "--no-build",
-                "--logger",
-                &format!("trx;LogFileName={}", trx_path.display()),
-                "--verbosity",
-                "minimal",
-            ]);
-
-            // Add filter if specific tests are selected
-            if let Some(tests) = test_filter {
-                if !tests.is_empty() {
-                    // Strip parameterized test arguments (everything in parentheses)
-                    // to avoid special character issues with MSBuild
-                    let filter = tests
-                        .iter()
-                        .map(|t| {
-                            let base_name = t.split('(').next().unwrap_or(t);
-                            format!("FullyQualifiedName~{}", base_name)
-                        })
-                        .collect::<Vec<_>>()
-                        .join("|");
-                    cmd.args(["--filter", &filter]);
-                }
+            let needs_build = match build_mode {
+                BuildMode::Always => true,
+                BuildMode::Stale => is_build_stale(&project_path, options.configuration.as_deref()),
+                BuildMode::Never => false,
+            };
+            if needs_build && !build_before_run(&project_path, &options, &tx, &token) {
+                return;
             }
 
-            cmd.arg(&project_path);
-            cmd.current_dir(project_dir);
-
-            // Log the command for diagnostics
-            let cmd_display = format!(
-                "dotnet test {} {}",
-                project_path.display(),
-                if cmd.get_args().any(|a| a.to_string_lossy().contains("--filter")) { "(with filter)" } else { "" }
-            );
-            let _ = tx.send(ExecutorEvent::OutputLine(format!("> {}", cmd_display)));
-
-            let mut child = match cmd
-                .stdout(Stdio::piped())
-                .stderr(Stdio::null())
-                .spawn()
-            {
-                Ok(child) => child,
-                Err(e) => {
-                    let _ = tx.send(ExecutorEvent::Error(format!("Failed to start dotnet: {}", e)));
-                    return;
-                }
+            let filters: Vec<Option<String>> = match test_filter {
+                Some(tests) if !tests.is_empty() => tests_filters(&tests).into_iter().map(Some).collect(),
+                _ => vec![None],
             };
+            let batches = filters.len();
+            // Stops when dropped at the end of the run
+            let _watchdog = timeout.map(|limit| start_watchdog(limit.0, token.clone()));
 
-            // Stream stdout, filtering build noise
-            if let Some(stdout) = child.stdout.take() {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines().map_while(|l| l.ok()) {
-                    if should_show_line(&line)
-                        && tx.send(ExecutorEvent::OutputLine(line)).is_err() {
-                            return;
+            let mut report = TestReport::default();
+            let mut coverage: Option<CoverageReport> = None;
+            for (i, filter) in filters.iter().enumerate() {
+                if token.is_cancelled() {
+                    let _ = tx.send(stopped_event(&token, timeout, options.collectors.blame));
+                    return;
+                }
+                let batch = (batches > 1).then_some((i + 1, batches));
+                match run_batch(&project_path, &options, filter.as_deref(), batch, &tx, &token) {
+                    BatchOutcome::Report(batch_report, batch_coverage) => {
+                        report.merge(batch_report);
+                        if let Some(batch_coverage) = batch_coverage {
+                            coverage.get_or_insert_with(CoverageReport::default).merge(batch_coverage);
                         }
+                    }
+                    BatchOutcome::Failed(msg) => {
+                        let msg = match batch {
+                            Some((n, total)) => format!("Batch {}/{}: {}", n, total, msg),
+                            None => msg,
+                        };
+                        let _ = tx.send(ExecutorEvent::Error(msg));
+                        return;
+                    }
+                    BatchOutcome::Cancelled => {
+                        let _ = tx.send(stopped_event(&token, timeout, options.collectors.blame));
+                        return;
+                    }
+                    BatchOutcome::Stopped => return,
                 }
             }
-
-            // Wait for completion
-            let status = child.wait();
-
-            // Parse TRX results
-            match std::fs::read_to_string(&trx_path) {
-                Ok(content) => match parse_trx(&content) {
-                    Ok(results) => {
-                        let _ = tx.send(ExecutorEvent::Completed(results));
+            if options.collectors.coverage {
+                match coverage {
+                    Some(coverage) => {
+                        let _ = tx.send(ExecutorEvent::Coverage(coverage));
                     }
-                    Err(e) => {
-                        let _ = tx.send(ExecutorEvent::Error(format!("TRX parse error: {}", e)));
-                    }
-                },
-                Err(_) => {
-                    // TRX file not created - dotnet test likely failed before producing results
-                    let mut msg = String::from("dotnet test did not produce results.");
-                    if let Ok(s) = &status {
-                        if !s.success() {
-                            msg.push_str(&format!(" Exit code: {}", s.code().unwrap_or(-1)));
-                        }
+                    None => {
+                        let _ = tx.send(ExecutorEvent::OutputLine(OutputLine::new(
+                            OutputSource::Testament,
+                            "[Coverage] No coverage was collected. Does the test project reference coverlet.collector?"
+                                .to_string(),
+                        )));
                     }
-                    let _ = tx.send(ExecutorEvent::Error(msg));
                 }
             }
-
-            // Cleanup
-            let _ = std::fs::remove_file(&trx_path);
+            let _ = tx.send(ExecutorEvent::Completed(report));
         });
 
-        rx
+        ExecutionHandle { events: rx, cancel }
+    }
+}
+
+/// Time the run out after `limit`, killing `dotnet test`. The watchdog gives up
+/// once the returned sender is dropped.
+fn start_watchdog(limit: Duration, token: CancelToken) -> Sender<()> {
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    thread::spawn(move || {
+        if let Err(RecvTimeoutError::Timeout) = done_rx.recv_timeout(limit) {
+            token.time_out();
+        }
+    });
+    done_tx
+}
+
+/// `Cancelled`, or an error when the run was killed for taking too long.
+fn stopped_event(token: &CancelToken, timeout: Option<TimeLimit>, blame: Blame) -> ExecutorEvent {
+    match timeout {
+        Some(limit) if token.is_timed_out() => {
+            let mut msg = format!("Test run timed out after {} and was killed.", limit);
+            if blame.hang_timeout.is_none() {
+                msg.push_str(" Set blame_hang_timeout in .testament.toml to find the test that hangs.");
+            }
+            ExecutorEvent::Error(msg)
+        }
+        _ => ExecutorEvent::Cancelled,
+    }
+}
+
+/// Serializes builds: projects of a solution-wide run share referenced projects,
+/// and concurrent builds of the same project fight over its `obj` directory.
+static BUILD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Build before a test run. Returns whether tests should run; otherwise the
+/// failure or cancellation has been sent.
+fn build_before_run(project_path: &Path, options: &BatchOptions, tx: &Sender<ExecutorEvent>, token: &CancelToken) -> bool {
+    let _ = tx.send(ExecutorEvent::BuildStarted);
+    let args = [split_run_settings(&options.extra_args).0, &options.profile.args].concat();
+    let error = match run_build(
+        project_path,
+        options.configuration.as_deref(),
+        &args,
+        &options.profile.env,
+        tx,
+        token,
+    ) {
+        BuildOutcome::Succeeded => return true,
+        BuildOutcome::Failed => ExecutorEvent::Error("Build failed; tests were not run.".to_string()),
+        BuildOutcome::Cancelled => ExecutorEvent::Cancelled,
+        BuildOutcome::NotStarted(msg) => ExecutorEvent::Error(msg),
+    };
+    let _ = tx.send(error);
+    false
+}
+
+/// Send each non-empty stderr line as it arrives, wrapped by `event`. Runs on its
+/// own thread so neither pipe can fill up and stall the process.
+fn forward_stderr(
+    stderr: ChildStderr,
+    tx: Sender<ExecutorEvent>,
+    event: fn(OutputLine) -> ExecutorEvent,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(|l| l.ok()) {
+            if !line.trim().is_empty() {
+                let _ = tx.send(event(OutputLine::new(OutputSource::Stderr, line)));
+            }
+        }
+    })
+}
+
+enum BuildOutcome {
+    Succeeded,
+    Failed,
+    Cancelled,
+    NotStarted(String),
+}
+
+/// `dotnet test` options that `dotnet build` rejects, and whether each takes a value.
+const TEST_ONLY_OPTIONS: &[(&str, bool)] = &[
+    ("--filter", true),
+    ("--logger", true),
+    ("-l", true),
+    ("--settings", true),
+    ("-s", true),
+    ("--results-directory", true),
+    ("--collect", true),
+    ("--test-adapter-path", true),
+    ("--diag", true),
+    ("--environment", true),
+    ("--blame", false),
+    ("--blame-crash", false),
+    ("--blame-crash-dump-type", true),
+    ("--blame-hang", false),
+    ("--blame-hang-dump-type", true),
+    ("--blame-hang-timeout", true),
+];
+
+/// The arguments that also apply to `dotnet build`: test-only options (with
+/// their values) and inline RunSettings after `--` are left out.
+fn build_args(args: &[String]) -> Vec<&String> {
+    let mut kept = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        // `--logger:trx` and `--logger=trx` carry their value
+        let name = arg.split([':', '=']).next().unwrap_or(arg);
+        match TEST_ONLY_OPTIONS.iter().find(|(option, _)| *option == name) {
+            Some((_, takes_value)) => {
+                if *takes_value && name == arg {
+                    iter.next();
+                }
+            }
+            None => kept.push(arg),
+        }
+    }
+    kept
+}
+
+/// Split extra arguments at `--` into the options before it and the inline
+/// RunSettings from `--` on, which have to come last on a `dotnet test` command line.
+pub(super) fn split_run_settings(extra_args: &[String]) -> (&[String], &[String]) {
+    match extra_args.iter().position(|arg| arg == "--") {
+        Some(i) => extra_args.split_at(i),
+        None => (extra_args, &[]),
+    }
+}
+
+/// Run `dotnet build`, sending each line of output as it arrives.
+fn run_build(
+    project_path: &Path,
+    configuration: Option<&str>,
+    args: &[String],
+    env: &BTreeMap<String, String>,
+    tx: &Sender<ExecutorEvent>,
+    token: &CancelToken,
+) -> BuildOutcome {
+    let _guard = BUILD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if token.is_cancelled() {
+        return BuildOutcome::Cancelled;
+    }
+
+    let project_dir = project_path.parent().unwrap_or(Path::new("."));
+    let mut cmd = dotnet_command();
+    cmd.args(["build", "--verbosity", "minimal"]);
+    if let Some(configuration) = configuration {
+        cmd.args(["--configuration", configuration]);
+    }
+    cmd.args(build_args(args));
+    cmd.envs(env);
+    let mut child = match new_process_group(&mut cmd)
+        .arg(project_path)
+        .current_dir(project_dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return BuildOutcome::NotStarted(format!("Failed to start dotnet: {}", e)),
+    };
+    token.attach(&child);
+
+    // MSBuild reports errors on stdout; forward stderr as it arrives too
+    let stderr_thread = child.stderr.take().map(|stderr| forward_stderr(stderr, tx.clone(), ExecutorEvent::BuildOutput));
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(|l| l.ok()) {
+            // Keep every diagnostic, even if it looks like test noise (`Assert.` in a message)
+            if parse_diagnostic(&line).is_some() || should_show_line(&line) {
+                let _ = tx.send(ExecutorEvent::BuildOutput(OutputLine::new(OutputSource::Stdout, line)));
+            }
+        }
+    }
+    if let Some(handle) = stderr_thread {
+        let _ = handle.join();
+    }
+
+    let status = child.wait();
+    token.detach();
+    if token.is_cancelled() {
+        BuildOutcome::Cancelled
+    } else if status.is_ok_and(|s| s.success()) {
+        BuildOutcome::Succeeded
+    } else {
+        BuildOutcome::Failed
     }
 }
 
+enum BatchOutcome {
+    /// Results, and coverage when it was collected and found
+    Report(TestReport, Option<CoverageReport>),
+    Failed(String),
+    /// Cancelled by the user or the run timeout
+    Cancelled,
+    /// Nobody is listening any more
+    Stopped,
+}
+
+/// Fail the blamed tests that have no result of their own in `report`.
+fn add_blamed(report: &mut TestReport, blamed: &[BlamedTest]) {
+    let reported: Vec<String> = report
+        .results
+        .iter()
+        .map(|r| report.qualified_name(r).unwrap_or_else(|| r.test_name.clone()))
+        .collect();
+    let missing: Vec<BlamedTest> = blamed.iter().filter(|b| !reported.contains(&b.name)).cloned().collect();
+    report.merge(blame_report(&missing));
+}
+
+/// How each `dotnet test` invocation of a run is made.
+struct BatchOptions {
+    format: ResultFormat,
+    collectors: Collectors,
+    configuration: Option<String>,
+    extra_args: Vec<String>,
+    profile: RunProfile,
+    debug: bool,
+}
+
+/// The `dotnet test` command for one invocation of a run.
+fn batch_command(
+    project_path: &Path,
+    options: &BatchOptions,
+    filter: Option<&str>,
+    results_path: &Path,
+    attachments_dir: Option<&Path>,
+) -> Command {
+    let (format, profile) = (options.format, &options.profile);
+    let project_dir = project_path.parent().unwrap_or(Path::new("."));
+    let mut cmd = dotnet_command();
+    cmd.args([
+        "test",
+        "--no-build",
+        "--logger",
+        &format.logger_arg(results_path),
+        // Print a line per finished test (not only failures) for live status
+        "--logger",
+        "console;verbosity=normal",
+        "--verbosity",
+        "minimal",
+    ]);
+
+    // Add filter if specific tests are selected
+    if let Some(filter) = filter {
+        cmd.args(["--filter", filter]);
+    }
+
+    // Must match the build's, or --no-build looks for the assembly in the wrong bin/ directory
+    if let Some(configuration) = &options.configuration {
+        cmd.args(["--configuration", configuration]);
+    }
+    if let Some(settings) = &profile.settings {
+        cmd.arg("--settings").arg(settings);
+    }
+    for logger in &profile.loggers {
+        cmd.args(["--logger", logger]);
+    }
+    let (extra_options, run_settings) = split_run_settings(&options.extra_args);
+    cmd.args(extra_options);
+    cmd.args(&profile.args);
+    cmd.envs(&profile.env);
+    if options.debug {
+        cmd.env("VSTEST_HOST_DEBUG", "1");
+    }
+
+    if let Some(dir) = attachments_dir {
+        cmd.arg("--results-directory").arg(dir);
+        cmd.args(options.collectors.for_run(options.debug).args());
+    }
+
+    cmd.arg(project_path);
+    // Inline RunSettings must come after every option
+    cmd.args(run_settings);
+    cmd.current_dir(project_dir);
+    cmd
+}
+
+/// Run one `dotnet test` invocation, streaming its live results to `tx`.
+fn run_batch(
+    project_path: &Path,
+    options: &BatchOptions,
+    filter: Option<&str>,
+    batch: Option<(usize, usize)>,
+    tx: &Sender<ExecutorEvent>,
+    token: &CancelToken,
+) -> BatchOutcome {
+    let (format, collectors) = (options.format, options.collectors.for_run(options.debug));
+    // Unique results path per run to avoid stale results from crashes
+    let results_path = std::env::temp_dir().join(format!(
+        "testament_{}_{}.{}",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+        format.extension()
+    ));
+    // Remove any stale file
+    let _ = std::fs::remove_file(&results_path);
+
+    // Attachments go to a directory of their own so they can be found afterwards
+    let attachments_dir = collectors.enabled().then(|| results_path.with_extension("attachments"));
+    let mut cmd = batch_command(project_path, options, filter, &results_path, attachments_dir.as_deref());
+
+    // Log the command for diagnostics
+    let cmd_display = format!(
+        "dotnet test {} {}",
+        project_path.display(),
+        match (filter, batch) {
+            (_, Some((n, total))) => format!("(with filter, batch {}/{})", n, total),
+            (Some(_), None) => "(with filter)".to_string(),
+            (None, None) => String::new(),
+        }
+    );
+    let _ = tx.send(ExecutorEvent::OutputLine(OutputLine::new(
+        OutputSource::Testament,
+        format!("> {}", cmd_display),
+    )));
+
+    let mut child = match new_process_group(&mut cmd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return BatchOutcome::Failed(format!("Failed to start dotnet: {}", e)),
+    };
+    token.attach(&child);
+
+    // testhost crashes and assembly load failures only show up on stderr
+    let stderr_thread = child.stderr.take().map(|stderr| forward_stderr(stderr, tx.clone(), ExecutorEvent::OutputLine));
+
+    // Stream stdout, filtering build noise
+    if let Some(stdout) = child.stdout.take() {
+        let reader = BufReader::new(stdout);
+        for line in reader.lines().map_while(|l| l.ok()) {
+            let event = if let Some(result) = parse_result_line(&line) {
+                ExecutorEvent::TestFinished(result)
+            } else if let Some(pid) = parse_debug_process_id(&line) {
+                ExecutorEvent::DebuggerWaiting(pid)
+            } else if should_show_line(&line) {
+                ExecutorEvent::OutputLine(OutputLine::new(OutputSource::Stdout, line))
+            } else {
+                continue;
+            };
+            if tx.send(event).is_err() {
+                // Nobody is listening any more; don't leave testhost behind
+                token.cancel();
+                let _ = child.wait();
+                let _ = std::fs::remove_file(&results_path);
+                return BatchOutcome::Stopped;
+            }
+        }
+    }
+    if let Some(handle) = stderr_thread {
+        let _ = handle.join();
+    }
+
+    // Wait for completion
+    let status = child.wait();
+    token.detach();
+    if token.is_cancelled() {
+        let _ = std::fs::remove_file(&results_path);
+        if let Some(dir) = &attachments_dir {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+        return BatchOutcome::Cancelled;
+    }
+
+    let blamed = attachments_dir.as_deref().map(collect_blame).unwrap_or_default();
+    let coverage = attachments_dir.as_deref().filter(|_| collectors.coverage).and_then(collect_coverage);
+    for test in &blamed {
+        let _ = tx.send(ExecutorEvent::OutputLine(OutputLine::new(
+            OutputSource::Testament,
+            format!("[Blame] {}: {}", test.name, test.message().replace('\n', " ")),
+        )));
+    }
+    // Keep the directory only for the dumps it holds
+    if let Some(dir) = &attachments_dir {
+        if blamed.iter().all(|test| test.dump.is_none()) {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    // Stream results rather than reading the whole file into memory
+    let outcome = match File::open(&results_path) {
+        Ok(file) => match parse_report(BufReader::new(file), format, &ParseOptions::default()) {
+            Ok(mut report) => {
+                add_blamed(&mut report, &blamed);
+                BatchOutcome::Report(report, coverage)
+            }
+            Err(e) => BatchOutcome::Failed(e.to_string()),
+        },
+        // testhost died before the logger wrote anything
+        Err(_) if !blamed.is_empty() => BatchOutcome::Report(blame_report(&blamed), coverage),
+        Err(_) => {
+            // Results file not created - dotnet test likely failed before producing results
+            let mut msg = String::from("dotnet test did not produce results.");
+            if let Ok(s) = &status {
+                if !s.success() {
+                    msg.push_str(&format!(" Exit code: {}", s.code().unwrap_or(-1)));
+                }
+            }
+            BatchOutcome::Failed(msg)
+        }
+    };
+
+    // Cleanup
+    let _ = std::fs::remove_file(&results_path);
+    outcome
+}
+
 /// Filter out verbose build output, keeping only test-relevant lines
 fn should_show_line(line: &str) -> bool {
     let trimmed = line.trim();
@@ -244,3 +813,65 @@ fn should_show_line(line: &str) -> bool {
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_args_drop_test_only_options() {
+        let args: Vec<String> = [
+            "--no-restore", "--blame", "--logger", "trx", "-p:Foo=Bar", "--settings:ci.runsettings",
+            "--arch", "x64", "--blame-hang-timeout=5m", "--", "MSTest.Parallelize.Workers=1",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(build_args(&args), ["--no-restore", "-p:Foo=Bar", "--arch", "x64"]);
+    }
+
+    #[test]
+    fn test_batch_command_puts_run_settings_last() {
+        let options = BatchOptions {
+            format: ResultFormat::default(),
+            collectors: Collectors { blame: Blame { crash: true, hang_timeout: None }, coverage: false },
+            configuration: Some("Release".to_string()),
+            extra_args: vec!["--no-restore".to_string(), "--".to_string(), "RunConfiguration.X=1".to_string()],
+            profile: RunProfile { args: vec!["--arch".to_string(), "x64".to_string()], ..Default::default() },
+            debug: false,
+        };
+        let project = Path::new("/src/App.Tests/App.Tests.csproj");
+        let cmd = batch_command(project, &options, Some("Name=Add"), Path::new("/tmp/r.trx"), Some(Path::new("/tmp/r")));
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        let position = |arg: &str| args.iter().position(|a| a == arg).unwrap();
+
+        assert!(position("--no-restore") < position("--arch"));
+        assert!(position("--arch") < position("--results-directory"));
+        assert!(position("--blame-crash") < position("/src/App.Tests/App.Tests.csproj"));
+        assert_eq!(args[args.len() - 3..], ["/src/App.Tests/App.Tests.csproj", "--", "RunConfiguration.X=1"]);
+    }
+
+    #[test]
+    fn test_build_skips_run_settings() {
+        let extra_args = ["-p:Foo=Bar".to_string(), "--".to_string(), "RunConfiguration.X=1".to_string()];
+        let profile_args = ["--no-restore".to_string(), "--blame".to_string()];
+        let args = [split_run_settings(&extra_args).0, &profile_args].concat();
+        assert_eq!(build_args(&args), ["-p:Foo=Bar", "--no-restore"]);
+    }
+
+    #[test]
+    fn test_collector_args_skip_hang_timeout_when_debugging() {
+        let collectors = Collectors {
+            blame: Blame { crash: true, hang_timeout: Some("5m".parse().unwrap()) },
+            coverage: true,
+        };
+        assert_eq!(
+            collectors.for_run(false).args(),
+            ["--blame-crash", "--blame-hang", "--blame-hang-timeout", "5m", "--collect", "XPlat Code Coverage"]
+        );
+        assert_eq!(collectors.for_run(true).args(), ["--blame-crash", "--collect", "XPlat Code Coverage"]);
+
+        let hang_only = Collectors { blame: Blame { crash: false, hang_timeout: Some("5m".parse().unwrap()) }, coverage: false };
+        assert!(!hang_only.for_run(true).enabled());
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+/// Longest `--filter` passed to a single `dotnet test`. The filter is forwarded
+/// through MSBuild to the test host, and Windows caps a whole command line at
+/// 32,767 characters.
+const MAX_FILTER_LEN: usize = 8000;
+
+/// `--filter` expressions running exactly the given tests, by fully-qualified name,
+/// split into batches of at most `MAX_FILTER_LEN` characters. Each batch needs its
+/// own `dotnet test` invocation.
+///
+/// Names are matched with `FullyQualifiedName=`. A parameterized case such as
+/// `Ns.Class.Method(1, "a")` runs every case of its method: adapters disagree on
+/// whether arguments are part of the fully-qualified name, so the arguments
+/// can't be matched reliably.
+pub fn tests_filters<S: AsRef<str>>(names: impl IntoIterator<Item = S>) -> Vec<String> {
+    let mut terms = Vec::new();
+    for name in names {
+        let name = name.as_ref();
+        match name.split_once('(') {
+            Some((method, _)) => terms.extend(method_terms(method)),
+            None => terms.push(format!("FullyQualifiedName={}", escape_filter_value(name))),
+        }
+    }
+    pack_terms(dedup(terms), MAX_FILTER_LEN)
+}
+
+/// `--filter` expressions running the given test methods, including all of their
+/// parameterized cases, batched like `tests_filters`.
+pub fn methods_filters<S: AsRef<str>>(methods: impl IntoIterator<Item = S>) -> Vec<String> {
+    pack_terms(dedup(methods.into_iter().flat_map(|m| method_terms(m.as_ref()))), MAX_FILTER_LEN)
+}
+
+/// The method itself (xUnit reports theories without arguments), and its cases
+/// with arguments (NUnit, MSTest).
+fn method_terms(method: &str) -> [String; 2] {
+    let method = escape_filter_value(method);
+    [
+        format!("FullyQualifiedName={}", method),
+        format!("FullyQualifiedName~{}\\(", method),
+    ]
+}
+
+fn dedup(terms: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    terms.into_iter().filter(|t| seen.insert(t.clone())).collect()
+}
+
+/// Join terms with `|` into as few filters as fit in `max_len` each. A single
+/// term longer than `max_len` gets a filter of its own.
+fn pack_terms(terms: Vec<String>, max_len: usize) -> Vec<String> {
+    let mut filters: Vec<String> = Vec::new();
+    for term in terms {
+        match filters.last_mut() {
+            Some(filter) if filter.len() + 1 + term.len() <= max_len => {
+                filter.push('|');
+                filter.push_str(&term);
+            }
+            _ => filters.push(term),
+        }
+    }
+    filters
+}
+
+/// Escape a value for a VSTest filter condition.
+///
+/// Filter operators (`\ ( ) & | = ! ~`) are escaped with a backslash. `dotnet test`
+/// passes the filter on as an MSBuild property, where `,` and `;` separate values,
+/// so those (and `%`) are MSBuild-escaped.
+fn escape_filter_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '(' | ')' | '&' | '|' | '=' | '!' | '~' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '%' => escaped.push_str("%25"),
+            ',' => escaped.push_str("%2C"),
+            ';' => escaped.push_str("%3B"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tests_filters_is_exact() {
+        assert_eq!(
+            tests_filters(["Ns.Tests.Foo.Bar", "Ns.Tests.Foo.Baz"]),
+            ["FullyQualifiedName=Ns.Tests.Foo.Bar|FullyQualifiedName=Ns.Tests.Foo.Baz"]
+        );
+    }
+
+    #[test]
+    fn test_tests_filters_parameterized_cases_share_method_terms() {
+        assert_eq!(
+            tests_filters(["Ns.Foo.Parse(\"a|b\")", "Ns.Foo.Parse(\"x\")"]),
+            ["FullyQualifiedName=Ns.Foo.Parse|FullyQualifiedName~Ns.Foo.Parse\\("]
+        );
+    }
+
+    #[test]
+    fn test_tests_filters_batches_long_selections() {
+        let names: Vec<String> = (0..800).map(|i| format!("Ns.Tests.FailingTests.Test{:03}", i)).collect();
+        let filters = tests_filters(&names);
+        assert!(filters.len() > 1);
+        assert!(filters.iter().all(|f| f.len() <= MAX_FILTER_LEN));
+        let terms: Vec<&str> = filters.iter().flat_map(|f| f.split('|')).collect();
+        assert_eq!(terms.len(), 800);
+        assert_eq!(terms[799], "FullyQualifiedName=Ns.Tests.FailingTests.Test799");
+    }
+
+    #[test]
+    fn test_pack_terms() {
+        let terms = ["aaa", "bb", "cccc", "dddddddd"].map(String::from).to_vec();
+        assert_eq!(pack_terms(terms, 7), ["aaa|bb", "cccc", "dddddddd"]);
+    }
+
+    #[test]
+    fn test_methods_filters() {
+        assert_eq!(
+            methods_filters(["Ns.Foo.Add"]),
+            ["FullyQualifiedName=Ns.Foo.Add|FullyQualifiedName~Ns.Foo.Add\\("]
+        );
+
+        let methods: Vec<String> = (0..400).map(|i| format!("Ns.Tests.ChangedTests.Test{:03}", i)).collect();
+        let filters = methods_filters(&methods);
+        assert!(filters.len() > 1);
+        assert!(filters.iter().all(|f| f.len() <= MAX_FILTER_LEN));
+        assert_eq!(filters.iter().flat_map(|f| f.split('|')).count(), 800);
+    }
+
+    #[test]
+    fn test_escape_filter_value() {
+        assert_eq!(escape_filter_value("Ns.Foo.Bar"), "Ns.Foo.Bar");
+        assert_eq!(escape_filter_value(r"a|b&c=d!e~f\g"), r"a\|b\&c\=d\!e\~f\\g");
+        assert_eq!(escape_filter_value("Generic<Int32,String>.Test"), "Generic<Int32%2CString>.Test");
+        assert_eq!(escape_filter_value("50%;done"), "50%25%3Bdone");
+    }
+
+    #[test]
+    fn test_tests_filters_escapes_names() {
+        assert_eq!(
+            tests_filters(["Ns.Foo`1.Bar!=Baz"]),
+            [r"FullyQualifiedName=Ns.Foo`1.Bar\!\=Baz"]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, TestamentError};
+use crate::model::{RunSnapshot, Test, TestProject, TestStatus};
+use crate::parser::{parse_report, ParseOptions, ResultFormat, TestOutcome, TestReport, TestResult};
+
+use super::discovery::build_test_classes;
+
+/// Load one or more result files (e.g. CI artifacts) into browsable projects.
+/// TRX, xUnit, NUnit3 and JUnit XML files are accepted and can be mixed.
+///
+/// Tests are grouped into a project per test assembly and a class per declaring
+/// class, both taken from the test definitions. Results without a definition
+/// fall back to a project named after the result file.
+pub fn load_result_projects(paths: &[PathBuf]) -> Result<Vec<TestProject>> {
+    // project name -> (assembly path, class full name -> tests)
+    let mut projects: BTreeMap<String, (PathBuf, HashMap<String, Vec<Test>>)> = BTreeMap::new();
+
+    for path in paths {
+        let report = read_report(path)?;
+        let fallback_name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Results")
+            .to_string();
+
+        for result in &report.results {
+            let definition = result
+                .test_id
+                .as_deref()
+                .and_then(|id| report.definitions.get(id));
+            let (project_name, project_path) = match definition {
+                Some(d) if !d.storage.is_empty() => (assembly_name(&d.storage), PathBuf::from(&d.storage)),
+                _ => (fallback_name.clone(), path.clone()),
+            };
+            let class_full = definition.map(|d| d.class_name.clone()).unwrap_or_default();
+            let full_name = report
+                .qualified_name(result)
+                .unwrap_or_else(|| result.test_name.clone());
+
+            let entry = projects
+                .entry(project_name)
+                .or_insert_with(|| (project_path, HashMap::new()));
+            entry.1.entry(class_full.clone()).or_default().push(test_from_result(result, full_name, &class_full));
+        }
+    }
+
+    Ok(projects
+        .into_iter()
+        .map(|(name, (path, classes))| {
+            let mut project = TestProject::new(name, path);
+            project.classes = build_test_classes(classes);
+            project
+        })
+        .collect())
+}
+
+/// Load a result file as a snapshot for comparing runs, keyed by the
+/// fully-qualified names resolved from its test definitions.
+pub fn load_snapshot(path: &Path) -> Result<RunSnapshot> {
+    let report = read_report(path)?;
+    let mut snapshot = RunSnapshot::default();
+    for result in &report.results {
+        let name = report
+            .qualified_name(result)
+            .unwrap_or_else(|| result.test_name.clone());
+        snapshot.insert(name, status_from_outcome(&result.outcome), Some(result.duration_ms));
+    }
+    Ok(snapshot)
+}
+
+fn read_report(path: &Path) -> Result<TestReport> {
+    let format = ResultFormat::detect(path)?;
+    let file = File::open(path).map_err(|e| TestamentError::FileRead {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    parse_report(BufReader::new(file), format, &ParseOptions::default())
+        .map_err(|e| TestamentError::ResultParse(format!("{}: {}", path.display(), e)))
+}
+
+/// Assembly name from a storage path, e.g. `C:\src\Api.Tests\bin\Api.Tests.dll` -> `Api.Tests`.
+/// Split manually so Windows paths from CI agents work on any host.
+fn assembly_name(storage: &str) -> String {
+    let file_name = storage.rsplit(['/', '\\']).next().unwrap_or(storage);
+    file_name
+        .strip_suffix(".dll")
+        .or_else(|| file_name.strip_suffix(".exe"))
+        .unwrap_or(file_name)
+        .to_string()
+}
+
+/// Build a finished test from a result. The display name is `Class.Method`, as for
+/// tests discovered with fully-qualified names.
+fn test_from_result(result: &TestResult, full_name: String, class_full: &str) -> Test {
+    let display_name = if !class_full.is_empty() && full_name.starts_with(class_full) {
+        match class_full.rsplit_once('.') {
+            Some((namespace, _)) => full_name[namespace.len() + 1..].to_string(),
+            None => full_name.clone(),
+        }
+    } else {
+        result.test_name.clone()
+    };
+    let mut test = Test::new(display_name, full_name);
+    test.status = status_from_outcome(&result.outcome);
+    test.duration_ms = Some(result.duration_ms);
+    test.error_message = result.error_message.clone();
+    test.output = result.output.clone();
+    test
+}
+
+fn status_from_outcome(outcome: &TestOutcome) -> TestStatus {
+    match outcome {
+        TestOutcome::Passed => TestStatus::Passed,
+        TestOutcome::Failed => TestStatus::Failed,
+        TestOutcome::Skipped => TestStatus::Skipped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const SAMPLE_TRX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <TestRun>
+            <Results>
+                <UnitTestResult testId="a" testName="Calc.Tests.MathTests.Add" outcome="Passed" duration="00:00:00.0100000" />
+                <UnitTestResult testId="b" testName="Calc.Tests.MathTests.Divide" outcome="Failed" duration="00:00:00.0200000">
+                    <Output><ErrorInfo><Message>Divide by zero</Message></ErrorInfo></Output>
+                </UnitTestResult>
+                <UnitTestResult testId="c" testName="Parse(&quot;x&quot;)" outcome="NotExecuted" />
+            </Results>
+            <TestDefinitions>
+                <UnitTest name="Add" storage="C:\src\Calc.Tests\bin\Debug\net8.0\Calc.Tests.dll" id="a">
+                    <TestMethod className="Calc.Tests.MathTests" name="Add" />
+                </UnitTest>
+                <UnitTest name="Divide" storage="C:\src\Calc.Tests\bin\Debug\net8.0\Calc.Tests.dll" id="b">
+                    <TestMethod className="Calc.Tests.MathTests" name="Divide" />
+                </UnitTest>
+                <UnitTest name="Parse" storage="/build/Parser.Tests/bin/Parser.Tests.dll" id="c">
+                    <TestMethod className="Parser.Tests.ParseTests" name="Parse" />
+                </UnitTest>
+            </TestDefinitions>
+        </TestRun>"#;
+
+    #[test]
+    fn test_assembly_name() {
+        assert_eq!(assembly_name(r"C:\src\Api.Tests\bin\Api.Tests.dll"), "Api.Tests");
+        assert_eq!(assembly_name("/src/Core.Tests/bin/Core.Tests.dll"), "Core.Tests");
+        assert_eq!(assembly_name("Plain"), "Plain");
+    }
+
+    #[test]
+    fn test_load_result_projects_groups_by_assembly_and_class() {
+        let temp_dir = TempDir::new().unwrap();
+        let trx = temp_dir.path().join("results.trx");
+        fs::write(&trx, SAMPLE_TRX).unwrap();
+
+        let projects = load_result_projects(&[trx]).unwrap();
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].name, "Calc.Tests");
+        assert_eq!(projects[1].name, "Parser.Tests");
+
+        let class = &projects[0].classes[0];
+        assert_eq!(class.full_name, "Calc.Tests.MathTests");
+        assert_eq!(class.tests.len(), 2);
+        assert_eq!(class.tests[0].name, "MathTests.Add");
+        assert_eq!(class.tests[0].status, TestStatus::Passed);
+        assert_eq!(class.tests[1].full_name, "Calc.Tests.MathTests.Divide");
+        assert_eq!(class.tests[1].status, TestStatus::Failed);
+        assert_eq!(class.tests[1].error_message.as_deref(), Some("Divide by zero"));
+
+        let parse = &projects[1].classes[0].tests[0];
+        assert_eq!(parse.full_name, "Parser.Tests.ParseTests.Parse(\"x\")");
+        assert_eq!(parse.status, TestStatus::Skipped);
+    }
+
+    #[test]
+    fn test_load_result_projects_without_definitions_uses_file_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let trx = temp_dir.path().join("nightly.trx");
+        fs::write(&trx, r#"<TestRun><Results><UnitTestResult testName="Test1" outcome="Passed" /></Results></TestRun>"#).unwrap();
+
+        let projects = load_result_projects(&[trx]).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "nightly");
+        assert_eq!(projects[0].test_count(), 1);
+    }
+
+    #[test]
+    fn test_load_result_projects_mixes_formats() {
+        let temp_dir = TempDir::new().unwrap();
+        let trx = temp_dir.path().join("results.trx");
+        fs::write(&trx, SAMPLE_TRX).unwrap();
+        let junit = temp_dir.path().join("junit.xml");
+        fs::write(
+            &junit,
+            r#"<testsuites><testsuite name="Api.Tests.dll"><testcase classname="Api.Tests.UsersTests" name="Get" time="0.5"><failure message="404"/></testcase></testsuite></testsuites>"#,
+        )
+        .unwrap();
+
+        let projects = load_result_projects(&[trx, junit]).unwrap();
+        let names: Vec<_> = projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Api.Tests", "Calc.Tests", "Parser.Tests"]);
+
+        let get = &projects[0].classes[0].tests[0];
+        assert_eq!(get.name, "UsersTests.Get");
+        assert_eq!(get.full_name, "Api.Tests.UsersTests.Get");
+        assert_eq!(get.status, TestStatus::Failed);
+        assert_eq!(get.duration_ms, Some(500));
+    }
+
+    #[test]
+    fn test_load_snapshot_diffs_by_qualified_name() {
+        use crate::model::{diff_runs, ChangeKind};
+
+        let temp_dir = TempDir::new().unwrap();
+        let before = temp_dir.path().join("before.trx");
+        fs::write(&before, SAMPLE_TRX).unwrap();
+        let after = temp_dir.path().join("after.trx");
+        fs::write(
+            &after,
+            SAMPLE_TRX
+                .replace(r#"testName="Calc.Tests.MathTests.Add" outcome="Passed""#, r#"testName="Calc.Tests.MathTests.Add" outcome="Failed""#)
+                .replace(r#"testName="Calc.Tests.MathTests.Divide" outcome="Failed""#, r#"testName="Calc.Tests.MathTests.Divide" outcome="Passed""#),
+        )
+        .unwrap();
+
+        let changes = diff_runs(&load_snapshot(&before).unwrap(), &load_snapshot(&after).unwrap());
+        let kinds: Vec<_> = changes.iter().map(|c| (c.name.as_str(), c.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                ("Calc.Tests.MathTests.Add", ChangeKind::NewFailure),
+                ("Calc.Tests.MathTests.Divide", ChangeKind::Fixed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_result_projects_missing_file() {
+        let result = load_result_projects(&[PathBuf::from("/nonexistent/results.trx")]);
+        assert!(matches!(result, Err(TestamentError::FileRead { .. })));
+    }
+}
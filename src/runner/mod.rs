@@ -1,7 +1,21 @@
+mod blame;
+mod coverage;
 mod discovery;
+mod dotnet;
 mod executor;
+mod filter;
+mod import;
+mod process;
+mod scheduler;
 mod watcher;
 
-pub use discovery::{discover_projects_lazy, discover_projects_from_paths, find_solution, find_csproj_in_dir, DiscoveryEvent};
-pub use executor::{ExecutorEvent, TestExecutor};
+pub use discovery::{
+    affected_projects, discover_projects_lazy, discover_projects_from_paths, find_csproj_for_file, find_csproj_in_dir,
+    find_solution, DiscoveryEvent,
+};
+pub use dotnet::{dotnet_command, sdk_status, set_dotnet_host};
+pub use executor::{ExecutionHandle, ExecutorEvent, TestExecutor};
+pub use filter::methods_filters;
+pub use import::{load_result_projects, load_snapshot};
+pub use scheduler::ProjectQueue;
 pub use watcher::FileWatcher;
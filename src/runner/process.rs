@@ -0,0 +1,187 @@
+use std::process::{Child, Command};
+use std::sync::{Arc, Mutex};
+
+/// Lets a build or test run be cancelled from the UI thread while a worker
+/// thread owns the `dotnet` child process.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<Mutex<TokenState>>,
+}
+
+#[derive(Default)]
+struct TokenState {
+    pid: Option<u32>,
+    cancelled: bool,
+    timed_out: bool,
+}
+
+impl CancelToken {
+    /// Record the spawned process so `cancel` can kill it. If the run was
+    /// cancelled before the process started, it is killed right away.
+    pub(super) fn attach(&self, child: &Child) {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if state.cancelled {
+            kill_process_tree(child.id());
+        } else {
+            state.pid = Some(child.id());
+        }
+    }
+
+    /// Forget the process once it has been waited on, so a reused pid is never killed.
+    pub(super) fn detach(&self) {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).pid = None;
+    }
+
+    /// Kill the process and everything it started (testhost, vstest.console, ...).
+    pub fn cancel(&self) {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if state.cancelled {
+            return;
+        }
+        state.cancelled = true;
+        if let Some(pid) = state.pid {
+            kill_process_tree(pid);
+        }
+    }
+
+    /// Cancel because the run took too long rather than at the user's request.
+    pub(super) fn time_out(&self) {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if !state.cancelled {
+            state.timed_out = true;
+        }
+        drop(state);
+        self.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).cancelled
+    }
+
+    /// Whether the cancellation came from `time_out`.
+    pub(super) fn is_timed_out(&self) -> bool {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).timed_out
+    }
+}
+
+/// Start the command in its own process group so the whole tree can be killed.
+pub(super) fn new_process_group(cmd: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    cmd
+}
+
+#[cfg(unix)]
+fn kill_process_tree(pid: u32) {
+    // The child leads its own process group, so a negative pid signals all of it
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+fn kill_process_tree(pid: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .output();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_before_attach_is_remembered() {
+        let token = CancelToken::default();
+        assert!(!token.is_cancelled());
+        token.cancel();
+        assert!(token.is_cancelled());
+        // Cancelling twice is harmless
+        token.cancel();
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_time_out_after_cancel_is_a_cancel() {
+        let token = CancelToken::default();
+        token.time_out();
+        assert!(token.is_cancelled() && token.is_timed_out());
+
+        let token = CancelToken::default();
+        token.cancel();
+        token.time_out();
+        assert!(!token.is_timed_out());
+    }
+
+    #[test]
+    fn test_clones_share_state() {
+        let token = CancelToken::default();
+        let worker = token.clone();
+        token.cancel();
+        assert!(worker.is_cancelled());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cancel_kills_process_group() {
+        // The shell's child sleep stands in for testhost under dotnet
+        let dir = tempfile::TempDir::new().unwrap();
+        let pid_file = dir.path().join("sleep.pid");
+        let script = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+        let mut child = new_process_group(Command::new("sh").args(["-c", &script]))
+            .spawn()
+            .unwrap();
+        let pid = wait_for_pid(&pid_file);
+
+        let token = CancelToken::default();
+        token.attach(&child);
+        token.cancel();
+
+        assert!(!child.wait().unwrap().success());
+        assert!(wait_until_gone(pid), "sleep {} survived the cancel", pid);
+    }
+
+    #[cfg(unix)]
+    fn wait_for_pid(path: &std::path::Path) -> libc::pid_t {
+        for _ in 0..500 {
+            if let Some(pid) = std::fs::read_to_string(path).ok().and_then(|s| s.trim().parse().ok()) {
+                return pid;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("no pid written to {}", path.display());
+    }
+
+    /// Whether `pid` exits within a few seconds. An exited process that is not
+    /// reaped yet (its parent was killed too) counts as gone.
+    #[cfg(unix)]
+    fn wait_until_gone(pid: libc::pid_t) -> bool {
+        for _ in 0..500 {
+            // SAFETY: signal 0 only checks that the process exists
+            let exists = unsafe { libc::kill(pid, 0) } == 0;
+            let zombie = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+                .is_ok_and(|stat| stat.rsplit(')').next().is_some_and(|rest| rest.trim_start().starts_with('Z')));
+            if !exists || zombie {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_attach_after_cancel_kills_immediately() {
+        let token = CancelToken::default();
+        token.cancel();
+        let mut child = new_process_group(&mut Command::new("sleep"))
+            .arg("30")
+            .spawn()
+            .unwrap();
+        token.attach(&child);
+        assert!(!child.wait().unwrap().success());
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+
+/// Order in which a solution-wide run starts its projects, keeping at most
+/// `max_parallel` of them in flight.
+#[derive(Debug)]
+pub struct ProjectQueue {
+    pending: VecDeque<usize>,
+    in_flight: Vec<usize>,
+    max_parallel: usize,
+}
+
+impl ProjectQueue {
+    pub fn new(projects: impl IntoIterator<Item = usize>, max_parallel: usize) -> Self {
+        Self {
+            pending: projects.into_iter().collect(),
+            in_flight: Vec::new(),
+            max_parallel: max_parallel.max(1),
+        }
+    }
+
+    /// Next project to start, if there is one waiting and a free slot.
+    pub fn start_next(&mut self) -> Option<usize> {
+        if self.in_flight.len() >= self.max_parallel {
+            return None;
+        }
+        let idx = self.pending.pop_front()?;
+        self.in_flight.push(idx);
+        Some(idx)
+    }
+
+    /// Free the slot of a project that has finished (or failed to start).
+    pub fn finish(&mut self, idx: usize) {
+        self.in_flight.retain(|&i| i != idx);
+    }
+
+    /// Drop projects that haven't started, returning them.
+    pub fn clear_pending(&mut self) -> Vec<usize> {
+        self.pending.drain(..).collect()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty() && self.in_flight.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_next_respects_max_parallel() {
+        let mut queue = ProjectQueue::new([0, 1, 2], 2);
+        assert_eq!(queue.start_next(), Some(0));
+        assert_eq!(queue.start_next(), Some(1));
+        assert_eq!(queue.start_next(), None);
+
+        queue.finish(0);
+        assert_eq!(queue.start_next(), Some(2));
+        assert_eq!(queue.start_next(), None);
+        assert!(!queue.is_done());
+
+        queue.finish(1);
+        queue.finish(2);
+        assert!(queue.is_done());
+    }
+
+    #[test]
+    fn test_zero_parallel_runs_sequentially() {
+        let mut queue = ProjectQueue::new([4, 7], 0);
+        assert_eq!(queue.start_next(), Some(4));
+        assert_eq!(queue.start_next(), None);
+    }
+
+    #[test]
+    fn test_clear_pending() {
+        let mut queue = ProjectQueue::new([0, 1, 2], 1);
+        assert_eq!(queue.start_next(), Some(0));
+        assert_eq!(queue.clear_pending(), [1, 2]);
+        assert_eq!(queue.start_next(), None);
+        queue.finish(0);
+        assert!(queue.is_done());
+    }
+
+    #[test]
+    fn test_empty_queue_is_done() {
+        let mut queue = ProjectQueue::new([], 4);
+        assert!(queue.is_done());
+        assert_eq!(queue.start_next(), None);
+    }
+}
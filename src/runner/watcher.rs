@@ -1,54 +1,182 @@
-use std::path::Path;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver};
 use std::time::{Duration, Instant};
 
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::Gitignore;
+use ignore::{Match, WalkBuilder};
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher, Event};
 
+use crate::config::WatchConfig;
+
 pub struct FileWatcher {
     _watcher: RecommendedWatcher,
-    rx: Receiver<()>,
+    rx: Receiver<(Instant, Vec<PathBuf>)>,
+    root: PathBuf,
+    debounce: Duration,
+    pending: BTreeSet<PathBuf>,
+    last_change: Option<Instant>,
 }
 
 impl FileWatcher {
-    pub fn new(watch_path: &Path) -> notify::Result<Self> {
+    pub fn new(watch_path: &Path, config: &WatchConfig) -> Result<Self, String> {
         let (tx, rx) = mpsc::channel();
-        let debounce_duration = Duration::from_millis(500);
-        let mut last_event = Instant::now() - debounce_duration;
+        let root = std::fs::canonicalize(watch_path).unwrap_or_else(|_| watch_path.to_path_buf());
+        let filter = WatchFilter::new(&root, config)?;
 
         let mut watcher = RecommendedWatcher::new(
             move |result: Result<Event, notify::Error>| {
                 if let Ok(event) = result {
                     // Only trigger on file modifications
-                    if event.kind.is_modify() || event.kind.is_create() {
-                        // Check for relevant file extensions
-                        let is_relevant = event.paths.iter().any(|p| {
-                            p.extension()
-                                .map(|ext| ext == "cs" || ext == "csproj")
-                                .unwrap_or(false)
-                        });
-
-                        if is_relevant {
-                            let now = Instant::now();
-                            if now.duration_since(last_event) >= debounce_duration {
-                                last_event = now;
-                                let _ = tx.send(());
-                            }
+                    if event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove() {
+                        let paths: Vec<PathBuf> = event.paths.into_iter().filter(|p| filter.matches(p)).collect();
+                        if !paths.is_empty() {
+                            let _ = tx.send((Instant::now(), paths));
                         }
                     }
                 }
             },
             Config::default(),
-        )?;
+        )
+        .map_err(|e| e.to_string())?;
 
-        watcher.watch(watch_path, RecursiveMode::Recursive)?;
+        watcher.watch(&root, RecursiveMode::Recursive).map_err(|e| e.to_string())?;
 
         Ok(Self {
             _watcher: watcher,
             rx,
+            root,
+            debounce: config.debounce(),
+            pending: BTreeSet::new(),
+            last_change: None,
         })
     }
 
-    pub fn try_recv(&self) -> bool {
-        self.rx.try_recv().is_ok()
+    /// The watched directory, canonicalized like the changed paths.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Files changed since the last call, once none has changed for the debounce
+    /// time. Empty while changes are still coming in.
+    pub fn changed_paths(&mut self) -> Vec<PathBuf> {
+        for (at, paths) in self.rx.try_iter() {
+            self.pending.extend(paths);
+            self.last_change = Some(at);
+        }
+        match self.last_change {
+            Some(at) if at.elapsed() >= self.debounce => {
+                self.last_change = None;
+                std::mem::take(&mut self.pending).into_iter().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Decides which changed files trigger a run: `[watch] patterns`, minus
+/// `[watch] ignore` and, optionally, files ignored by `.gitignore`.
+struct WatchFilter {
+    root: PathBuf,
+    patterns: GlobSet,
+    ignore: GlobSet,
+    /// Deepest directory first, so nested files override their parents
+    gitignores: Vec<Gitignore>,
+}
+
+impl WatchFilter {
+    fn new(root: &Path, config: &WatchConfig) -> Result<Self, String> {
+        let gitignores = if config.gitignore { gitignore_files(root) } else { Vec::new() };
+        Ok(Self {
+            root: root.to_path_buf(),
+            patterns: glob_set(&config.patterns)?,
+            ignore: glob_set(&config.ignore)?,
+            gitignores,
+        })
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        self.patterns.is_match(relative) && !self.ignore.is_match(relative) && !self.is_gitignored(path)
+    }
+
+    fn is_gitignored(&self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        for gitignore in self.gitignores.iter().filter(|g| path.starts_with(g.path())) {
+            match gitignore.matched_path_or_any_parents(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+}
+
+/// `/` only matches `/`, like in `.gitignore`: `*.cs` is a file at the root.
+fn glob_set(globs: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = GlobBuilder::new(glob).literal_separator(true).build().map_err(|e| e.to_string())?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// `.gitignore` files in `root`'s tree (skipping ignored directories) and in its
+/// parents up to the repository root.
+fn gitignore_files(root: &Path) -> Vec<Gitignore> {
+    let mut files: Vec<PathBuf> = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name() == ".gitignore")
+        .map(|entry| entry.into_path())
+        .collect();
+    for dir in root.ancestors().skip(1) {
+        files.push(dir.join(".gitignore"));
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    let mut gitignores: Vec<Gitignore> =
+        files.iter().filter(|file| file.is_file()).map(|file| Gitignore::new(file).0).collect();
+    gitignores.sort_by_key(|gitignore| std::cmp::Reverse(gitignore.path().components().count()));
+    gitignores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_watch_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/Generated")).unwrap();
+        fs::write(root.join(".gitignore"), "Generated/\n*.g.cs\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "!Keep.g.cs\n").unwrap();
+
+        let filter = WatchFilter::new(root, &WatchConfig::default()).unwrap();
+        assert!(filter.matches(&root.join("src/App.cs")));
+        assert!(filter.matches(&root.join("src/App.csproj")));
+        assert!(!filter.matches(&root.join("src/README.md")));
+        assert!(!filter.matches(&root.join("src/obj/Debug/App.AssemblyInfo.cs")));
+        assert!(!filter.matches(&root.join("src/Generated/Client.cs")));
+        assert!(!filter.matches(&root.join("src/Api.g.cs")));
+        assert!(filter.matches(&root.join("src/Keep.g.cs")));
+
+        let config = WatchConfig { patterns: vec!["src/*.cs".to_string()], gitignore: false, ..WatchConfig::default() };
+        let filter = WatchFilter::new(root, &config).unwrap();
+        assert!(filter.matches(&root.join("src/Api.g.cs")));
+        assert!(!filter.matches(&root.join("src/Nested/App.cs")));
+        assert!(!filter.matches(&root.join("App.cs")));
     }
 }
@@ -0,0 +1,178 @@
+use std::path::Path;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::parser::{CoverageCounts, CoverageReport};
+use crate::ui::theme::Theme;
+
+/// A row of the coverage view: an assembly with its totals, or one of its source files.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoverageItem {
+    Assembly { name: String, counts: CoverageCounts },
+    /// Path relative to the directory holding all of the assembly's files
+    File { path: String, counts: CoverageCounts },
+}
+
+/// Group per-file coverage by assembly, files by path.
+pub fn coverage_items(report: &CoverageReport) -> Vec<CoverageItem> {
+    let by_file = report.by_file();
+    let mut items = Vec::new();
+    for (name, counts) in report.by_assembly() {
+        let files: Vec<(&String, &CoverageCounts)> =
+            by_file.iter().filter(|((assembly, _), _)| *assembly == name).map(|((_, file), c)| (file, c)).collect();
+        let root = common_dir(files.iter().map(|(file, _)| Path::new(file.as_str())));
+        items.push(CoverageItem::Assembly { name, counts });
+        for (file, counts) in files {
+            let path = Path::new(file).strip_prefix(&root).unwrap_or(Path::new(file));
+            items.push(CoverageItem::File { path: path.display().to_string(), counts: *counts });
+        }
+    }
+    items
+}
+
+/// Deepest directory containing every path.
+fn common_dir<'a>(mut paths: impl Iterator<Item = &'a Path>) -> std::path::PathBuf {
+    let Some(first) = paths.next() else { return Default::default() };
+    let mut root = first.parent().unwrap_or(Path::new("")).to_path_buf();
+    for path in paths {
+        while !path.starts_with(&root) {
+            if !root.pop() {
+                break;
+            }
+        }
+    }
+    root
+}
+
+/// Green from 80%, yellow from 50%, red below.
+pub fn coverage_style(theme: &Theme, percent: f64) -> Style {
+    let color = if percent >= 80.0 {
+        theme.passed
+    } else if percent >= 50.0 {
+        theme.running
+    } else {
+        theme.failed
+    };
+    Style::default().fg(color)
+}
+
+pub struct CoveragePane<'a> {
+    report: Option<&'a CoverageReport>,
+    items: &'a [CoverageItem],
+    theme: &'a Theme,
+    focused: bool,
+    collecting: bool,
+}
+
+impl<'a> CoveragePane<'a> {
+    pub fn new(
+        report: Option<&'a CoverageReport>,
+        items: &'a [CoverageItem],
+        theme: &'a Theme,
+        focused: bool,
+        collecting: bool,
+    ) -> Self {
+        Self { report, items, theme, focused, collecting }
+    }
+
+    fn counts_spans(&self, counts: &CoverageCounts) -> Vec<Span<'a>> {
+        let mut spans = Vec::new();
+        if let Some(percent) = counts.line_percent() {
+            spans.push(Span::styled(format!("{:>5.1}%", percent), coverage_style(self.theme, percent)));
+        } else {
+            spans.push(Span::raw("    - "));
+        }
+        match counts.branch_percent() {
+            Some(percent) => spans.push(Span::styled(format!(" {:>5.1}% ", percent), coverage_style(self.theme, percent))),
+            None => spans.push(Span::raw("     -  ")),
+        }
+        spans
+    }
+
+    fn item_line(&self, item: &CoverageItem) -> Line<'a> {
+        match item {
+            CoverageItem::Assembly { name, counts } => {
+                let mut spans = self.counts_spans(counts);
+                spans.push(Span::styled(name.clone(), Style::default().add_modifier(Modifier::BOLD)));
+                Line::from(spans)
+            }
+            CoverageItem::File { path, counts } => {
+                let mut spans = self.counts_spans(counts);
+                spans.push(Span::raw(format!("  {}", path)));
+                Line::from(spans)
+            }
+        }
+    }
+}
+
+impl StatefulWidget for CoveragePane<'_> {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let border_style = if self.focused {
+            Style::default().fg(self.theme.highlight)
+        } else {
+            Style::default().fg(self.theme.border)
+        };
+        let title = match self.report {
+            Some(report) => format!("Coverage ({})", report.totals().summary()),
+            None => "Coverage".to_string(),
+        };
+        let block = Block::default().borders(Borders::ALL).title(title).border_style(border_style);
+
+        if self.items.is_empty() {
+            let message = if self.collecting {
+                "No coverage yet. It is collected on the next run."
+            } else {
+                "No coverage yet. Press V to collect coverage on the next runs."
+            };
+            Paragraph::new(message)
+                .style(Style::default().fg(self.theme.fg))
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = self.items.iter().map(|item| ListItem::new(self.item_line(item))).collect();
+        let list = List::new(items)
+            .block(block)
+            .style(Style::default().fg(self.theme.fg))
+            .highlight_style(Style::default().fg(self.theme.highlight).add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+
+        StatefulWidget::render(list, area, buf, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_cobertura;
+
+    #[test]
+    fn test_coverage_items_groups_files_by_assembly() {
+        let xml = r#"<coverage><packages>
+  <package name="Api"><classes>
+    <class name="Api.UserService" filename="/src/Api/Services/UserService.cs"><lines>
+      <line number="1" hits="1" /><line number="2" hits="0" />
+    </lines></class>
+    <class name="Api.User" filename="/src/Api/Models/User.cs"><lines>
+      <line number="1" hits="1" />
+    </lines></class>
+  </classes></package>
+</packages></coverage>"#;
+        let report = parse_cobertura(xml.as_bytes()).unwrap();
+        let items = coverage_items(&report);
+
+        assert_eq!(items.len(), 3);
+        assert!(matches!(&items[0], CoverageItem::Assembly { name, counts } if name == "Api" && counts.lines_valid == 3));
+        assert!(matches!(&items[1], CoverageItem::File { path, .. } if path == "Models/User.cs"));
+        assert!(matches!(&items[2], CoverageItem::File { path, counts } if path == "Services/UserService.cs" && counts.lines_covered == 1));
+    }
+}
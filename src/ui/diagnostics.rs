@@ -0,0 +1,162 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::parser::{Diagnostic, Severity};
+use crate::ui::theme::Theme;
+
+/// A row of the diagnostics view: a project with its counts, or one of its diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticItem {
+    Project { name: String, errors: usize, warnings: usize },
+    /// Index into the diagnostics list
+    Diagnostic(usize),
+}
+
+/// Group diagnostics by project (in the order projects first reported one),
+/// errors before warnings.
+pub fn diagnostic_items(diagnostics: &[Diagnostic]) -> Vec<DiagnosticItem> {
+    let mut projects: Vec<(String, Vec<usize>)> = Vec::new();
+    for (idx, diagnostic) in diagnostics.iter().enumerate() {
+        let name = diagnostic.project_name();
+        match projects.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, indices)) => indices.push(idx),
+            None => projects.push((name, vec![idx])),
+        }
+    }
+
+    let mut items = Vec::new();
+    for (name, mut indices) in projects {
+        indices.sort_by_key(|&i| diagnostics[i].severity);
+        let (errors, warnings) = count_severities(indices.iter().map(|&i| &diagnostics[i]));
+        items.push(DiagnosticItem::Project { name, errors, warnings });
+        items.extend(indices.into_iter().map(DiagnosticItem::Diagnostic));
+    }
+    items
+}
+
+/// (errors, warnings)
+pub fn count_severities<'a>(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> (usize, usize) {
+    diagnostics.into_iter().fold((0, 0), |(errors, warnings), d| match d.severity {
+        Severity::Error => (errors + 1, warnings),
+        Severity::Warning => (errors, warnings + 1),
+    })
+}
+
+/// "2 errors, 1 warning"
+pub fn describe_counts(errors: usize, warnings: usize) -> String {
+    let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+    format!("{}, {}", plural(errors, "error"), plural(warnings, "warning"))
+}
+
+pub struct DiagnosticsPane<'a> {
+    diagnostics: &'a [Diagnostic],
+    items: &'a [DiagnosticItem],
+    theme: &'a Theme,
+    focused: bool,
+}
+
+impl<'a> DiagnosticsPane<'a> {
+    pub fn new(diagnostics: &'a [Diagnostic], items: &'a [DiagnosticItem], theme: &'a Theme, focused: bool) -> Self {
+        Self { diagnostics, items, theme, focused }
+    }
+
+    fn item_line(&self, item: &DiagnosticItem) -> Line<'a> {
+        match item {
+            DiagnosticItem::Project { name, errors, warnings } => Line::from(vec![
+                Span::styled(name.clone(), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format!("  {}", describe_counts(*errors, *warnings))),
+            ]),
+            DiagnosticItem::Diagnostic(idx) => {
+                let Some(d) = self.diagnostics.get(*idx) else {
+                    return Line::default();
+                };
+                let (symbol, color) = match d.severity {
+                    Severity::Error => ("x", self.theme.failed),
+                    Severity::Warning => ("!", self.theme.running),
+                };
+                // File name only; the full path is what Enter opens
+                let location = d.location();
+                let location = location.rsplit(['/', '\\']).next().unwrap_or_default().to_string();
+                Line::from(vec![
+                    Span::styled(format!("  {} {} ", symbol, d.code), Style::default().fg(color)),
+                    Span::styled(location, Style::default().add_modifier(Modifier::DIM)),
+                    Span::raw(format!(" {}", d.message)),
+                ])
+            }
+        }
+    }
+}
+
+impl StatefulWidget for DiagnosticsPane<'_> {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let border_style = if self.focused {
+            Style::default().fg(self.theme.highlight)
+        } else {
+            Style::default().fg(self.theme.border)
+        };
+        let (errors, warnings) = count_severities(self.diagnostics);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Diagnostics ({})", describe_counts(errors, warnings)))
+            .border_style(border_style);
+
+        if self.items.is_empty() {
+            Paragraph::new("No build errors or warnings.")
+                .style(Style::default().fg(self.theme.fg))
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = self.items.iter().map(|item| ListItem::new(self.item_line(item))).collect();
+        let list = List::new(items)
+            .block(block)
+            .style(Style::default().fg(self.theme.fg))
+            .highlight_style(Style::default().fg(self.theme.highlight).add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+
+        StatefulWidget::render(list, area, buf, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_diagnostic;
+
+    #[test]
+    fn test_diagnostic_items_groups_by_project() {
+        let diagnostics: Vec<Diagnostic> = [
+            "/src/Api/A.cs(1,1): warning CS0168: unused [/src/Api/Api.csproj]",
+            "/src/Api.Tests/T.cs(2,2): error CS0103: missing [/src/Api.Tests/Api.Tests.csproj]",
+            "/src/Api/B.cs(3,3): error CS1002: ; expected [/src/Api/Api.csproj]",
+        ]
+        .iter()
+        .map(|line| parse_diagnostic(line).unwrap())
+        .collect();
+
+        assert_eq!(
+            diagnostic_items(&diagnostics),
+            [
+                DiagnosticItem::Project { name: "Api".to_string(), errors: 1, warnings: 1 },
+                DiagnosticItem::Diagnostic(2),
+                DiagnosticItem::Diagnostic(0),
+                DiagnosticItem::Project { name: "Api.Tests".to_string(), errors: 1, warnings: 0 },
+                DiagnosticItem::Diagnostic(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_describe_counts() {
+        assert_eq!(describe_counts(1, 0), "1 error, 0 warnings");
+        assert_eq!(describe_counts(2, 1), "2 errors, 1 warning");
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use ratatui::{
@@ -8,9 +8,13 @@ use ratatui::{
     Frame,
 };
 
-use crate::model::{Test, TestClass, TestProject};
+use crate::config::Config;
+use crate::model::{OutputLine, OutputSource, RunSnapshot, Test, TestClass, TestProject};
+use crate::parser::{parse_diagnostic, CoverageCounts, CoverageReport, Diagnostic};
+use crate::ui::coverage::{coverage_items, CoverageItem, CoveragePane};
+use crate::ui::diagnostics::{diagnostic_items, DiagnosticItem, DiagnosticsPane};
 use crate::ui::output::OutputPane;
-use crate::ui::projects::ProjectList;
+use crate::ui::projects::{ProjectList, ProjectRunStatus};
 use crate::ui::tests::{build_test_items, TestList, TestListItem};
 use crate::ui::test_result::TestResultPane;
 use crate::ui::theme::Theme;
@@ -102,6 +106,7 @@ pub struct AppState {
     pub project_state: ListState,
     pub test_state: ListState,
     pub output: String,
+    pub output_stderr_lines: BTreeSet<usize>, // Indices of output lines that came from stderr
     pub output_scroll: u16,
     pub output_visible_lines: u16,
     pub output_width: u16,
@@ -116,11 +121,24 @@ pub struct AppState {
     pub filter_active: bool,
     pub watch_mode: bool,
     pub last_failed: HashSet<String>,
+    pub live_results: HashSet<String>, // Tests whose status so far comes from console output
+    pub last_run: RunSnapshot, // Latest result per test this session, for run diffs
     pub test_progress: Option<(usize, usize)>,
     pub discovering: bool,
+    pub busy: bool, // A build or test run is in progress
+    pub view_only: bool, // Browsing loaded result files; running is disabled
+    pub config: Config, // Settings from .testament.toml
     pub status: String,
     pub context: Option<String>,
     pub running_project_idx: Option<usize>, // Track which project tests are running for
+    pub project_runs: HashMap<usize, ProjectRunStatus>, // Per-project progress of a solution-wide run
+    pub diagnostics: Vec<Diagnostic>, // Errors and warnings from the latest builds
+    pub show_diagnostics: bool, // Diagnostics view replaces the Output pane
+    pub diagnostic_state: ListState,
+    pub coverage: Option<CoverageReport>, // Coverage of the latest run that collected it
+    coverage_by_class: HashMap<String, CoverageCounts>, // Coverage by simple class name, for the test list
+    pub show_coverage: bool, // Coverage view replaces the Output pane
+    pub coverage_state: ListState,
     pub dirty: bool, // Only redraw when state has changed
     // Cached values for performance
     cached_output_lines: Option<(u16, usize)>, // (total_lines, output_len) - invalidated when output changes
@@ -141,6 +159,7 @@ impl AppState {
             project_state,
             test_state: ListState::default(),
             output: String::new(),
+            output_stderr_lines: BTreeSet::new(),
             output_scroll: 0,
             output_visible_lines: 0,  // Set during first draw
             output_width: 80,
@@ -155,11 +174,24 @@ impl AppState {
             filter_active: false,
             watch_mode: false,
             last_failed: HashSet::new(),
+            live_results: HashSet::new(),
+            last_run: RunSnapshot::default(),
             test_progress: None,
             discovering: false,
+            busy: false,
+            view_only: false,
+            config: Config::default(),
             status: "Ready".to_string(),
             context: None,
             running_project_idx: None,
+            project_runs: HashMap::new(),
+            diagnostics: Vec::new(),
+            show_diagnostics: false,
+            diagnostic_state: ListState::default(),
+            coverage: None,
+            coverage_by_class: HashMap::new(),
+            show_coverage: false,
+            coverage_state: ListState::default(),
             dirty: true, // Draw on first frame
             cached_output_lines: None,
             output_newline_count: 0,
@@ -193,6 +225,7 @@ impl AppState {
             }
             if trim_pos > 0 && trim_pos < self.output.len() {
                 self.output.replace_range(..trim_pos, "");
+                self.output_stderr_lines = self.output_stderr_lines.split_off(&skip).into_iter().map(|i| i - skip).collect();
                 self.output_newline_count = TRIM_TO;
                 self.cached_output_lines = None;
                 self.output_scroll = 0;
@@ -204,8 +237,78 @@ impl AppState {
         }
     }
 
+    /// Append a line of `dotnet` output on a new line, remembering stderr lines
+    /// so they can be shown distinctly.
+    pub fn append_output_line(&mut self, line: &OutputLine) {
+        self.append_output(&format!("\n{}", line.content));
+        if line.source == OutputSource::Stderr {
+            // Line indices count newlines, and may have shifted if the buffer was trimmed
+            let last = self.output_newline_count;
+            let first = last.saturating_sub(line.content.matches('\n').count());
+            self.output_stderr_lines.extend(first..=last);
+        }
+    }
+
+    /// Show a line of build output, and collect it if it is an error or warning.
+    /// MSBuild repeats diagnostics in its summary, so duplicates are dropped.
+    pub fn append_build_line(&mut self, line: &OutputLine) {
+        self.append_output_line(line);
+        self.record_diagnostic(&line.content);
+    }
+
+    /// Collect a build output line if it is an error or warning.
+    pub fn record_diagnostic(&mut self, line: &str) {
+        if let Some(diagnostic) = parse_diagnostic(line) {
+            if !self.diagnostics.contains(&diagnostic) {
+                self.diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    pub fn clear_diagnostics(&mut self) {
+        self.diagnostics.clear();
+        self.diagnostic_state.select(None);
+        self.dirty = true;
+    }
+
+    /// Rows of the diagnostics view
+    pub fn diagnostic_items(&self) -> Vec<DiagnosticItem> {
+        diagnostic_items(&self.diagnostics)
+    }
+
+    /// Diagnostic under the cursor in the diagnostics view
+    pub fn selected_diagnostic(&self) -> Option<&Diagnostic> {
+        let items = self.diagnostic_items();
+        match items.get(self.diagnostic_state.selected()?)? {
+            DiagnosticItem::Diagnostic(idx) => self.diagnostics.get(*idx),
+            DiagnosticItem::Project { .. } => None,
+        }
+    }
+
+    /// Show the coverage of a run, replacing the previous run's, or adding to it
+    /// when `merge` (another project of a solution-wide run).
+    pub fn set_coverage(&mut self, report: CoverageReport, merge: bool) {
+        let coverage = match (self.coverage.take(), merge) {
+            (Some(mut coverage), true) => {
+                coverage.merge(report);
+                coverage
+            }
+            _ => report,
+        };
+        self.coverage_by_class = coverage.by_simple_name();
+        self.coverage = Some(coverage);
+        self.coverage_state.select(None);
+        self.dirty = true;
+    }
+
+    /// Rows of the coverage view
+    pub fn coverage_items(&self) -> Vec<CoverageItem> {
+        self.coverage.as_ref().map(coverage_items).unwrap_or_default()
+    }
+
     pub fn clear_output(&mut self) {
         self.output.clear();
+        self.output_stderr_lines.clear();
         self.output_scroll = 0;
         self.output_newline_count = 0;
         self.cached_output_lines = None;
@@ -397,7 +500,8 @@ pub fn draw(frame: &mut Frame, state: &mut AppState) {
         &state.theme,
         state.active_pane == Pane::Projects,
         state.discovering,
-    );
+    )
+    .with_runs(&state.project_runs);
     frame.render_stateful_widget(project_list, chunks[0], &mut state.project_state);
 
     // Resolve selected test item from cache BEFORE borrowing classes
@@ -421,18 +525,41 @@ pub fn draw(frame: &mut Frame, state: &mut AppState) {
         &state.selected_tests,
         &state.filter,
         project_name,
-    );
+    )
+    .with_coverage(&state.coverage_by_class);
     frame.render_stateful_widget(test_list, chunks[1], &mut state.test_state);
 
-    // Right top: Output pane
-    let output_pane = OutputPane::new(
-        &state.output,
-        &state.theme,
-        state.active_pane == Pane::Output,
-        state.output_scroll,
-        state.test_progress,
-    );
-    frame.render_widget(output_pane, right_chunks[0]);
+    // Right top: Output pane, or the build diagnostics or coverage in its place
+    if state.show_coverage {
+        let items = state.coverage_items();
+        let coverage_pane = CoveragePane::new(
+            state.coverage.as_ref(),
+            &items,
+            &state.theme,
+            state.active_pane == Pane::Output,
+            state.config.runner.coverage,
+        );
+        frame.render_stateful_widget(coverage_pane, right_chunks[0], &mut state.coverage_state);
+    } else if state.show_diagnostics {
+        let items = state.diagnostic_items();
+        let diagnostics_pane = DiagnosticsPane::new(
+            &state.diagnostics,
+            &items,
+            &state.theme,
+            state.active_pane == Pane::Output,
+        );
+        frame.render_stateful_widget(diagnostics_pane, right_chunks[0], &mut state.diagnostic_state);
+    } else {
+        let output_pane = OutputPane::new(
+            &state.output,
+            &state.theme,
+            state.active_pane == Pane::Output,
+            state.output_scroll,
+            state.test_progress,
+        )
+        .with_stderr_lines(&state.output_stderr_lines);
+        frame.render_widget(output_pane, right_chunks[0]);
+    }
 
     // Right bottom: Test Result pane
     let (selected_test, context_message) = get_selected_test_with_context(state, classes, selected_item_info.as_ref());
@@ -450,23 +577,58 @@ pub fn draw(frame: &mut Frame, state: &mut AppState) {
         .constraints([Constraint::Min(0), Constraint::Length(state.status.len() as u16 + 2)])
         .split(status_area);
 
-    let watch_indicator = if state.watch_mode { "[WATCH] " } else { "" };
+    let mut watch_indicator = String::new();
+    if state.watch_mode {
+        watch_indicator.push_str(&format!("[WATCH {}] ", state.config.watch.target));
+    }
+    if let Some((profile, _)) = state.config.active_profile() {
+        watch_indicator.push_str(&format!("[PROFILE {}] ", profile));
+    }
+    if let Some(configuration) = state.config.configuration() {
+        watch_indicator.push_str(&format!("[CONFIG {}] ", configuration));
+    }
     let left_status = if state.filter_active {
         format!("{}Filter: {}_", watch_indicator, state.filter)
     } else {
         let selected_count = state.selected_tests.len();
         let failed_count = state.last_failed.len();
-        let mut parts = vec![
-            "q:quit",
-            "b:build",
-            "r:run",
-            "R:run-all",
-            "w:watch",
-            "Tab:switch",
-        ];
-        if failed_count > 0 {
+        let mut parts = if state.view_only {
+            vec!["q:quit", "Tab:switch"]
+        } else {
+            vec![
+                "q:quit",
+                "b:build",
+                "r:run",
+                "R:run-all",
+                "S:run-solution",
+                "D:debug",
+                "B:configuration",
+                "w:watch",
+                "Tab:switch",
+            ]
+        };
+        if failed_count > 0 && !state.view_only {
             parts.push("a:run-failed");
         }
+        if !state.config.profiles.is_empty() && !state.view_only {
+            parts.push("p:profile");
+        }
+        if state.watch_mode {
+            parts.push("W:watch-target");
+        }
+        if state.busy {
+            parts.insert(1, "k:cancel");
+        }
+        if state.show_diagnostics {
+            parts.extend(["d:output", "Enter:open"]);
+        } else if !state.diagnostics.is_empty() {
+            parts.push("d:diagnostics");
+        }
+        if state.show_coverage {
+            parts.push("v:output");
+        } else if state.coverage.is_some() {
+            parts.push("v:coverage");
+        }
         parts.extend(["Space:toggle", "c:expand/collapse", "C:clear-sel", "x:clear-out", "/:filter"]);
 
         let suffix = if selected_count > 0 {
@@ -790,6 +952,31 @@ mod tests {
         assert_eq!(state.output, "Test output\nMore output");
     }
 
+    #[test]
+    fn test_append_output_line_tracks_stderr() {
+        let mut state = AppState::new(vec![]);
+        state.append_output_line(&OutputLine::new(OutputSource::Testament, "> dotnet test"));
+        state.append_output_line(&OutputLine::new(OutputSource::Stderr, "Unhandled exception.\n   at Main()"));
+        state.append_output_line(&OutputLine::new(OutputSource::Stdout, "Passed!"));
+        assert_eq!(state.output_stderr_lines, BTreeSet::from([2, 3]));
+
+        // Trimming the buffer shifts the indices with it
+        for _ in 0..1000 {
+            state.append_output("\nline");
+        }
+        state.append_output_line(&OutputLine::new(OutputSource::Stderr, "crash"));
+        assert!(state.output_stderr_lines.contains(&1005));
+        for _ in 0..996 {
+            state.append_output("\nline");
+        }
+        assert_eq!(state.output_newline_count, 1000);
+        assert_eq!(state.output_stderr_lines, BTreeSet::from([4]));
+        assert_eq!(state.output.split('\n').nth(4), Some("crash"));
+
+        state.clear_output();
+        assert!(state.output_stderr_lines.is_empty());
+    }
+
     #[test]
     fn test_app_state_filter_modification() {
         let mut state = AppState::new(vec![]);
@@ -857,4 +1044,26 @@ mod tests {
         assert_eq!(state.projects[1].name, "Project2");
         assert_eq!(state.projects[1].test_count(), 3);
     }
+
+    #[test]
+    fn test_append_build_line_collects_diagnostics_once() {
+        let mut state = AppState::new(vec![]);
+        let error = OutputLine::new(
+            OutputSource::Stdout,
+            "/src/Api/Users.cs(3,9): error CS0103: The name 'x' does not exist [/src/Api/Api.csproj]",
+        );
+
+        state.append_build_line(&OutputLine::new(OutputSource::Stdout, "  Api -> /src/Api/bin/Debug/net8.0/Api.dll"));
+        state.append_build_line(&error);
+        // Repeated in MSBuild's summary
+        state.append_build_line(&error);
+
+        assert_eq!(state.diagnostics.len(), 1);
+        assert!(state.output.contains("error CS0103"));
+
+        state.diagnostic_state.select(Some(1));
+        assert_eq!(state.selected_diagnostic().map(|d| d.code.as_str()), Some("CS0103"));
+        state.diagnostic_state.select(Some(0));
+        assert!(state.selected_diagnostic().is_none());
+    }
 }
@@ -1,3 +1,5 @@
+mod coverage;
+mod diagnostics;
 pub mod layout;
 mod output;
 mod projects;
@@ -5,5 +7,7 @@ pub mod tests;
 mod test_result;
 mod theme;
 
+pub use diagnostics::{count_severities, describe_counts};
 pub use layout::{draw, Pane};
+pub use projects::ProjectRunStatus;
 pub use tests::TestListItem;
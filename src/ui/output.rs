@@ -1,7 +1,10 @@
+use std::collections::BTreeSet;
+
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::Style,
+    text::{Line, Text},
     widgets::{Block, Borders, Paragraph, Widget, Wrap},
 };
 
@@ -13,6 +16,7 @@ pub struct OutputPane<'a> {
     focused: bool,
     scroll: u16,
     progress: Option<(usize, usize)>,
+    stderr_lines: Option<&'a BTreeSet<usize>>,
 }
 
 impl<'a> OutputPane<'a> {
@@ -23,7 +27,27 @@ impl<'a> OutputPane<'a> {
         scroll: u16,
         progress: Option<(usize, usize)>,
     ) -> Self {
-        Self { content, theme, focused, scroll, progress }
+        Self { content, theme, focused, scroll, progress, stderr_lines: None }
+    }
+
+    /// Lines (by index) to show in the error color.
+    pub fn with_stderr_lines(mut self, lines: &'a BTreeSet<usize>) -> Self {
+        self.stderr_lines = Some(lines);
+        self
+    }
+
+    fn text(&self) -> Text<'a> {
+        match self.stderr_lines {
+            Some(stderr) if !stderr.is_empty() => {
+                let error = Style::default().fg(self.theme.failed);
+                self.content
+                    .split('\n')
+                    .enumerate()
+                    .map(|(i, line)| if stderr.contains(&i) { Line::styled(line, error) } else { Line::raw(line) })
+                    .collect()
+            }
+            _ => Text::raw(self.content),
+        }
     }
 }
 
@@ -53,14 +77,14 @@ impl Widget for OutputPane<'_> {
                     ..inner
                 };
 
-                let paragraph = Paragraph::new(self.content)
+                let paragraph = Paragraph::new(self.text())
                     .style(Style::default().fg(self.theme.fg))
                     .wrap(Wrap { trim: false })
                     .scroll((self.scroll, 0));
                 paragraph.render(content_area, buf);
 
                 let width = 20usize;
-                let filled = if total > 0 { (completed * width) / total } else { 0 };
+                let filled = (completed * width).checked_div(total).unwrap_or(0);
                 let bar_text = format!(
                     " [{}{}] {}/{}",
                     "\u{2588}".repeat(filled),
@@ -73,7 +97,7 @@ impl Widget for OutputPane<'_> {
                 bar_widget.render(bar_area, buf);
             }
         } else {
-            let paragraph = Paragraph::new(self.content)
+            let paragraph = Paragraph::new(self.text())
                 .style(Style::default().fg(self.theme.fg))
                 .wrap(Wrap { trim: false })
                 .scroll((self.scroll, 0));
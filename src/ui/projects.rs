@@ -1,24 +1,66 @@
+use std::collections::HashMap;
+
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Modifier, Style},
-    text::Line,
+    text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState, StatefulWidget},
 };
 
 use crate::model::TestProject;
 use crate::ui::theme::Theme;
 
+/// Where a project is in a solution-wide run, shown beside its name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectRunStatus {
+    Queued,
+    Running { completed: usize, total: usize },
+    Done { passed: usize, failed: usize, skipped: usize },
+    Error,
+    Cancelled,
+}
+
 pub struct ProjectList<'a> {
     projects: &'a [TestProject],
     theme: &'a Theme,
     focused: bool,
     discovering: bool,
+    runs: Option<&'a HashMap<usize, ProjectRunStatus>>,
 }
 
 impl<'a> ProjectList<'a> {
     pub fn new(projects: &'a [TestProject], theme: &'a Theme, focused: bool, discovering: bool) -> Self {
-        Self { projects, theme, focused, discovering }
+        Self { projects, theme, focused, discovering, runs: None }
+    }
+
+    /// Show per-project progress of a solution-wide run
+    pub fn with_runs(mut self, runs: &'a HashMap<usize, ProjectRunStatus>) -> Self {
+        self.runs = Some(runs);
+        self
+    }
+
+    fn run_badge(&self, status: &ProjectRunStatus) -> (String, Style) {
+        match status {
+            ProjectRunStatus::Queued => (" queued".to_string(), Style::default().fg(self.theme.border)),
+            ProjectRunStatus::Running { completed, total } => (
+                format!(" * {}/{}", completed, total),
+                Style::default().fg(self.theme.running),
+            ),
+            ProjectRunStatus::Done { failed, .. } if *failed > 0 => (
+                format!(" x {} failed", failed),
+                Style::default().fg(self.theme.failed),
+            ),
+            ProjectRunStatus::Done { passed, skipped, .. } => {
+                let mut badge = format!(" + {} passed", passed);
+                if *skipped > 0 {
+                    badge.push_str(&format!(", {} skipped", skipped));
+                }
+                (badge, Style::default().fg(self.theme.passed))
+            }
+            ProjectRunStatus::Error => (" x error".to_string(), Style::default().fg(self.theme.failed)),
+            ProjectRunStatus::Cancelled => (" cancelled".to_string(), Style::default().fg(self.theme.skipped)),
+        }
     }
 }
 
@@ -29,15 +71,20 @@ impl StatefulWidget for ProjectList<'_> {
         let items: Vec<ListItem> = self
             .projects
             .iter()
-            .map(|p| {
+            .enumerate()
+            .map(|(idx, p)| {
                 let count = p.test_count();
                 let display = if count == 0 && self.discovering {
                     format!("{} (...)", p.name)
                 } else {
                     format!("{} ({})", p.name, count)
                 };
-                let line = Line::from(display);
-                ListItem::new(line)
+                let mut spans = vec![Span::raw(display)];
+                if let Some(status) = self.runs.and_then(|runs| runs.get(&idx)) {
+                    let (badge, style) = self.run_badge(status);
+                    spans.push(Span::styled(badge, style));
+                }
+                ListItem::new(Line::from(spans))
             })
             .collect();
 
@@ -110,15 +110,43 @@ impl Widget for TestResultPane<'_> {
 
                 // Duration
                 if let Some(duration_ms) = test.duration_ms {
-                    let duration_str = if duration_ms >= 1000 {
-                        format!("{:.2}s", duration_ms as f64 / 1000.0)
+                    lines.push(Line::from(vec![
+                        Span::styled("Duration: ", Style::default().fg(self.theme.border)),
+                        Span::styled(format_duration(duration_ms), Style::default().fg(self.theme.fg)),
+                    ]));
+                }
+
+                // Repeated run statistics
+                if let Some(ref stats) = test.repeat {
+                    let color = if stats.is_flaky() {
+                        self.theme.running
+                    } else if stats.failed > 0 {
+                        self.theme.failed
                     } else {
-                        format!("{}ms", duration_ms)
+                        self.theme.passed
                     };
+                    let mut verdict = stats.summary();
+                    if stats.is_flaky() {
+                        verdict.push_str(" - FLAKY");
+                    }
                     lines.push(Line::from(vec![
-                        Span::styled("Duration: ", Style::default().fg(self.theme.border)),
-                        Span::styled(duration_str, Style::default().fg(self.theme.fg)),
+                        Span::styled("Repeated: ", Style::default().fg(self.theme.border)),
+                        Span::styled(verdict, Style::default().fg(color)),
                     ]));
+                    if let Some((min, median, max)) = stats.duration_spread() {
+                        lines.push(Line::from(vec![
+                            Span::styled("Spread: ", Style::default().fg(self.theme.border)),
+                            Span::styled(
+                                format!(
+                                    "{} min, {} median, {} max",
+                                    format_duration(min),
+                                    format_duration(median),
+                                    format_duration(max)
+                                ),
+                                Style::default().fg(self.theme.fg),
+                            ),
+                        ]));
+                    }
                 }
 
                 // Error message / stack trace
@@ -140,6 +168,23 @@ impl Widget for TestResultPane<'_> {
                     }
                 }
 
+                // Captured stdout
+                if let Some(ref output) = test.output {
+                    lines.push(Line::from(""));
+                    lines.push(Line::from(Span::styled(
+                        "Output:",
+                        Style::default()
+                            .fg(self.theme.border)
+                            .add_modifier(Modifier::BOLD),
+                    )));
+                    for line in output.lines() {
+                        lines.push(Line::from(Span::styled(
+                            line.to_string(),
+                            Style::default().fg(self.theme.fg),
+                        )));
+                    }
+                }
+
                 let text = Paragraph::new(lines)
                     .scroll((self.scroll, 0))
                     .wrap(Wrap { trim: false });
@@ -148,3 +193,11 @@ impl Widget for TestResultPane<'_> {
         }
     }
 }
+
+fn format_duration(duration_ms: u64) -> String {
+    if duration_ms >= 1000 {
+        format!("{:.2}s", duration_ms as f64 / 1000.0)
+    } else {
+        format!("{}ms", duration_ms)
+    }
+}
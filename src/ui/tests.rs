@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use ratatui::{
     buffer::Buffer,
@@ -8,7 +8,9 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, ListState, StatefulWidget},
 };
 
-use crate::model::{TestClass, TestStatus};
+use crate::model::{ChangeKind, TestClass, TestStatus};
+use crate::parser::{tested_class_coverage, CoverageCounts};
+use crate::ui::coverage::coverage_style;
 use crate::ui::theme::Theme;
 
 pub struct TestList<'a> {
@@ -20,6 +22,7 @@ pub struct TestList<'a> {
     filter: &'a str,
     filter_lower: String,
     project_name: &'a str,
+    coverage: Option<&'a HashMap<String, CoverageCounts>>,
 }
 
 impl<'a> TestList<'a> {
@@ -41,9 +44,17 @@ impl<'a> TestList<'a> {
             filter,
             filter_lower: filter.to_lowercase(),
             project_name,
+            coverage: None,
         }
     }
 
+    /// Show the line coverage of the class each test class tests, from coverage
+    /// counts by simple class name.
+    pub fn with_coverage(mut self, coverage: &'a HashMap<String, CoverageCounts>) -> Self {
+        self.coverage = (!coverage.is_empty()).then_some(coverage);
+        self
+    }
+
     fn status_symbol(&self, status: &TestStatus) -> (&str, Style) {
         match status {
             TestStatus::NotRun => (" ", Style::default().fg(self.theme.fg)),
@@ -54,6 +65,15 @@ impl<'a> TestList<'a> {
         }
     }
 
+    /// Badge for tests whose result changed since the previous run
+    fn change_badge(&self, change: Option<ChangeKind>) -> Option<(&'static str, Style)> {
+        match change? {
+            ChangeKind::NewFailure => Some(("[new failure] ", Style::default().fg(self.theme.failed).add_modifier(Modifier::BOLD))),
+            ChangeKind::Fixed => Some(("[fixed] ", Style::default().fg(self.theme.passed).add_modifier(Modifier::BOLD))),
+            _ => None,
+        }
+    }
+
     /// Get aggregate status for a test class
     fn class_status(&self, class: &TestClass) -> TestStatus {
         let mut has_failed = false;
@@ -145,7 +165,7 @@ impl StatefulWidget for TestList<'_> {
             count_buf.push_str(" (");
             count_buf.push_str(&test_count.to_string());
             count_buf.push(')');
-            let class_line = Line::from(vec![
+            let mut class_spans = vec![
                 Span::styled(
                     collapse_str,
                     Style::default().fg(self.theme.border),
@@ -161,7 +181,15 @@ impl StatefulWidget for TestList<'_> {
                     count_buf,
                     Style::default().fg(self.theme.border),
                 ),
-            ]);
+            ];
+            let line_percent = self
+                .coverage
+                .and_then(|coverage| tested_class_coverage(coverage, &class.name))
+                .and_then(|counts| counts.line_percent());
+            if let Some(percent) = line_percent {
+                class_spans.push(Span::styled(format!(" {:.0}%", percent), coverage_style(self.theme, percent)));
+            }
+            let class_line = Line::from(class_spans);
             items.push(ListItem::new(class_line));
 
             // Tests under this class (if not collapsed)
@@ -172,7 +200,7 @@ impl StatefulWidget for TestList<'_> {
                     let is_selected = self.selected.contains(&test.full_name);
                     let select_prefix = if is_selected { "    [x] " } else { "    [ ] " };
 
-                    let test_line = Line::from(vec![
+                    let mut spans = vec![
                         Span::styled(
                             select_prefix,
                             if is_selected {
@@ -182,9 +210,12 @@ impl StatefulWidget for TestList<'_> {
                             },
                         ),
                         Span::styled(format!("{} ", symbol), style),
-                        Span::styled(&test.name, Style::default().fg(self.theme.fg)),
-                    ]);
-                    items.push(ListItem::new(test_line));
+                    ];
+                    if let Some((badge, badge_style)) = self.change_badge(test.change) {
+                        spans.push(Span::styled(badge, badge_style));
+                    }
+                    spans.push(Span::styled(&test.name, Style::default().fg(self.theme.fg)));
+                    items.push(ListItem::new(Line::from(spans)));
                 }
             }
         }
@@ -275,6 +306,7 @@ pub fn build_test_items(
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use super::*;
     use crate::model::Test;
@@ -358,6 +390,7 @@ mod tests {
             TestListItem::Class(_) => (),
             _ => panic!("Expected Class"),
         }
+        #[allow(clippy::needless_range_loop)]
         for i in 1..4 {
             match &items[i] {
                 TestListItem::Test(_) => (),